@@ -0,0 +1,82 @@
+//! Decoding of the events logged by the record program
+use {
+    all2all_controller::event::RecordEvent,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    multisig::event::MultisigEvent,
+    solana_pubkey::Pubkey,
+};
+
+/// Event of the record program or of its embedded multisig
+#[derive(Debug, PartialEq)]
+pub(crate) enum Event {
+    Record(RecordEvent),
+    Multisig(MultisigEvent),
+}
+
+/// decode a single `sol_log_data` field, dispatching on the event tag
+fn decode_event(data: &[u8]) -> Option<Event> {
+    if *data.get(1)? >= MultisigEvent::FIRST_TAG {
+        MultisigEvent::unpack(data).ok().map(Event::Multisig)
+    } else {
+        RecordEvent::unpack(data).ok().map(Event::Record)
+    }
+}
+
+/// decode the events `program_id` logged in a transaction. Data logged by
+/// other programs, e.g. through CPIs, is skipped.
+pub(crate) fn decode_logs(program_id: &Pubkey, logs: &[String]) -> Vec<Event> {
+    let program_id = program_id.to_string();
+    let mut invoked = Vec::new();
+    let mut events = Vec::new();
+    for line in logs {
+        let Some(rest) = line.strip_prefix("Program ") else {
+            continue;
+        };
+        if let Some(data) = rest.strip_prefix("data: ") {
+            if invoked.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            // the fields of one `sol_log_data` call share a line, space separated
+            events.extend(
+                data.split(' ')
+                    .filter_map(|field| STANDARD.decode(field).ok())
+                    .filter_map(|field| decode_event(&field)),
+            );
+        } else if let Some((program, status)) = rest.split_once(' ') {
+            if status.starts_with("invoke [") {
+                invoked.push(program);
+            } else if status == "success" || status.starts_with("failed") {
+                invoked.pop();
+            }
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_program_logs() {
+        let program_id = all2all_controller::id();
+        let other_program = Pubkey::new_unique();
+        let record = Pubkey::new_unique();
+        let written = RecordEvent::RecordClosed { record };
+        let encoded = STANDARD.encode(written.pack());
+        let logs = [
+            format!("Program {program_id} invoke [1]"),
+            "Program log: RecordInstruction::CloseAccount".to_string(),
+            format!("Program {other_program} invoke [2]"),
+            format!("Program data: {encoded}"),
+            format!("Program {other_program} success"),
+            format!("Program data: {encoded}"),
+            format!("Program {program_id} success"),
+        ];
+
+        assert_eq!(
+            decode_logs(&program_id, &logs),
+            vec![Event::Record(written)]
+        );
+    }
+}
@@ -1,17 +1,21 @@
-use all2all_controller::instruction;
+mod events;
+
+use all2all_controller::{error::RecordError, instruction};
 use clap::{Parser, Subcommand};
+use multisig::error::MultisigError;
+use num_traits::FromPrimitive;
 use serde::Serialize;
-use solana_client::rpc_client::RpcClient;
+use solana_client::{client_error::ClientError, rpc_client::RpcClient};
 use solana_pubkey::Pubkey;
 #[allow(deprecated)]
 use solana_sdk::{
     commitment_config::CommitmentConfig,
-    signature::{Keypair, Signer},
-    system_instruction,
-    transaction::Transaction,
+    instruction::InstructionError,
+    signature::{Keypair, Signature, Signer},
+    transaction::{Transaction, TransactionError},
 };
-
-const RECORD_META_DATA_SIZE: usize = 33;
+use solana_transaction_status_client_types::UiTransactionEncoding;
+use std::str::FromStr;
 
 #[derive(Serialize, Debug)]
 #[repr(C)]
@@ -36,10 +40,24 @@ impl TestConfig {
     }
 }
 
-mod program_id {
-    solana_pubkey::declare_id!("recr1L3PCGKLbckBqMNcJhuuyU1zgo8nBhfLVsJNwr5");
-    //solana_pubkey::declare_id!("TEsTstY62jQ8BvQkasHh1q2WvKCujNaQDgrLfZwBsiH");
+/// describe a failed transaction, naming the program error it failed with
+fn describe_error(err: &ClientError) -> String {
+    let Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) =
+        err.get_transaction_error()
+    else {
+        return err.to_string();
+    };
+    let message = if code >= MultisigError::FIRST_CODE {
+        MultisigError::from_u32(code).map(|e| e.to_string())
+    } else {
+        RecordError::from_u32(code).map(|e| e.to_string())
+    };
+    match message {
+        Some(message) => format!("{err}: {message}"),
+        None => err.to_string(),
+    }
 }
+
 fn load_keypair_from_json(fname: &str) -> Keypair {
     // Load keypair for the payer
     let keypair_file = std::fs::File::open(fname).unwrap();
@@ -65,6 +83,10 @@ struct Commandline {
     /// RPC URL to send transactions through
     rpc_url: String,
 
+    #[arg(long, default_value_t = all2all_controller::id())]
+    /// Address the record program is deployed at, e.g. on a local validator
+    program_id: Pubkey,
+
     #[arg(long, default_value = "id.json")]
     /// Payer keypair that will pay for deployment
     payer_keypair: String,
@@ -88,6 +110,11 @@ enum Commands {
     Write {},
     /// Close the account
     Close {},
+    /// Print the events the program logged in a transaction
+    Events {
+        /// Signature of the transaction
+        signature: String,
+    },
 }
 
 #[tokio::main]
@@ -96,10 +123,6 @@ async fn main() {
     let client = RpcClient::new_with_commitment(cli.rpc_url, CommitmentConfig::confirmed());
 
     let record_size = std::mem::size_of::<TestConfig>();
-    let account_size = RECORD_META_DATA_SIZE + record_size;
-    let lamports = client
-        .get_minimum_balance_for_rent_exemption(account_size)
-        .unwrap();
 
     let payer_kp = load_keypair_from_json(&cli.payer_keypair);
 
@@ -110,47 +133,35 @@ async fn main() {
 
     match cli.command {
         Commands::Init {} => {
-            let create_account_instruction = system_instruction::create_account(
-                &payer_kp.pubkey(),
-                &storage_holder_kp.pubkey(),
-                lamports,
-                account_size as u64,
-                &program_id::ID,
-            );
-            let mut create_account = Transaction::new_with_payer(
-                &[create_account_instruction],
-                Some(&payer_kp.pubkey()),
-            );
-
-            create_account.sign(&[&payer_kp, &storage_holder_kp], recent_blockhash);
-            match client.send_and_confirm_transaction(&create_account) {
-                Ok(signature) => println!("Account created Transaction Signature: {}", signature),
-                Err(err) => eprintln!("Error sending Account create transaction: {}", err),
-            }
-
             let authority_pubkey = if let Some(authority_pubkey) = cli.authority_pubkey {
                 Pubkey::from_str_const(&authority_pubkey)
             } else {
                 payer_kp.pubkey()
             };
-            // Create the instruction to init the account
-            let instruction_init =
-                instruction::initialize(&storage_holder_kp.pubkey(), &authority_pubkey);
+            // Create the account and init it in one instruction
+            let instruction_init = instruction::create_and_initialize_with_program_id(
+                &cli.program_id,
+                &storage_holder_kp.pubkey(),
+                &authority_pubkey,
+                &payer_kp.pubkey(),
+                record_size as u64,
+            );
 
             let mut transaction =
                 Transaction::new_with_payer(&[instruction_init], Some(&payer_kp.pubkey()));
-            transaction.sign(&[&payer_kp], client.get_latest_blockhash().unwrap());
+            transaction.sign(&[&payer_kp, &storage_holder_kp], recent_blockhash);
 
             // Send and confirm the transaction
             match client.send_and_confirm_transaction(&transaction) {
                 Ok(signature) => println!("Transaction Init Signature: {}", signature),
-                Err(err) => eprintln!("Error sending Init transaction: {}", err),
+                Err(err) => eprintln!("Error sending Init transaction: {}", describe_error(&err)),
             }
         }
         Commands::Write {} => {
             // send instruction to write number into account
             let initial = TestConfig::new(cli.interval, cli.verify_signatures, cli.packet_size);
-            let instruction_write = instruction::write(
+            let instruction_write = instruction::write_with_program_id(
+                &cli.program_id,
                 &storage_holder_kp.pubkey(),
                 &payer_kp.pubkey(),
                 0,
@@ -164,7 +175,7 @@ async fn main() {
                 // Send and confirm the transaction
                 match client.send_and_confirm_transaction(&transaction) {
                     Ok(signature) => println!("Transaction Write Signature: {}", signature),
-                    Err(err) => eprintln!("Error sending transaction: {}", err),
+                    Err(err) => eprintln!("Error sending transaction: {}", describe_error(&err)),
                 }
             } else {
                 println!("Accounts: {:?}", transaction.message().account_keys);
@@ -181,7 +192,8 @@ async fn main() {
             }
         }
         Commands::Close {} => {
-            let instruction_close = instruction::close_account(
+            let instruction_close = instruction::close_account_with_program_id(
+                &cli.program_id,
                 &storage_holder_kp.pubkey(),
                 &payer_kp.pubkey(),
                 &payer_kp.pubkey(),
@@ -193,7 +205,20 @@ async fn main() {
             // Send and confirm the transaction
             match client.send_and_confirm_transaction(&transaction) {
                 Ok(signature) => println!("Transaction Close Signature: {}", signature),
-                Err(err) => eprintln!("Error sending transaction: {}", err),
+                Err(err) => eprintln!("Error sending transaction: {}", describe_error(&err)),
+            }
+        }
+        Commands::Events { signature } => {
+            let signature = Signature::from_str(&signature).expect("Invalid signature");
+            let transaction = client
+                .get_transaction(&signature, UiTransactionEncoding::Json)
+                .expect("Failed to fetch transaction");
+            let logs: Option<Vec<String>> = transaction
+                .transaction
+                .meta
+                .and_then(|meta| meta.log_messages.into());
+            for event in events::decode_logs(&cli.program_id, &logs.unwrap_or_default()) {
+                println!("{:?}", event);
             }
         }
     }
@@ -1,6 +1,7 @@
 //! Multisig configuration data
 use bytemuck::{Pod, Zeroable};
 use {
+    crate::error::MultisigError,
     solana_account_info::AccountInfo,
     solana_msg::msg,
     solana_program_error::{ProgramError, ProgramResult},
@@ -11,10 +12,95 @@ use {
 /// group size of signers
 pub const MAX_SIGNERS: usize = 10;
 
-/// Multisig config
+/// number of proposals a multisig can have open at the same time
+pub const MAX_ACTIVE_PROPOSALS: usize = 16;
+
+/// length of an Ethereum address
+pub const ETH_ADDRESS_LENGTH: usize = 20;
+
+/// Kind of account sitting in a signer slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum MemberKind {
+    /// Regular keypair, approves by signing the approval transaction
+    Key = 0,
+    /// Another multisig config, approves through one of its own executed proposals
+    Multisig = 1,
+    /// Ethereum address, approves with a secp256k1 signature over the
+    /// proposal digest. The 20-byte address is stored left-aligned in the
+    /// slot, see [`MultisigConfig::ethereum_slot`]
+    Ethereum = 2,
+}
+
+impl TryFrom<u8> for MemberKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Key),
+            1 => Ok(Self::Multisig),
+            2 => Ok(Self::Ethereum),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+/// Multisig config as laid out before account types, member kinds, the
+/// guardian and the proposal registry. Such configs are upgraded to
+/// [`MultisigConfig`] with [`crate::instructions::process_migrate_multisig`].
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 #[repr(C)]
+pub struct MultisigConfigV1 {
+    /// Struct version, always [`MultisigConfigV1::VERSION`]
+    pub version: u8,
+    /// Number of required approvals
+    pub threshold: u8,
+    /// Number of signers (must be <= MAX_SIGNERS)
+    pub signer_count: u8,
+    /// Signers list, all keypairs
+    pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl MultisigConfigV1 {
+    /// Version of the legacy layout
+    pub const VERSION: u8 = 1;
+
+    pub const SIZE: usize = 1 + 1 + 1 + 32 * MAX_SIGNERS;
+
+    /// reads a legacy config from the start of account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let config = data
+            .get(..Self::SIZE)
+            .and_then(|bytes| bytemuck::try_from_bytes::<Self>(bytes).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !config.is_initialized() {
+            msg!("Account is not an initialized v1 multisig config");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(*config)
+    }
+
+    /// the same signers and threshold in the current layout, every member
+    /// being a keypair
+    pub fn migrate(&self) -> Result<MultisigConfig, ProgramError> {
+        MultisigConfig::new(self.threshold, &self.signers[..self.signer_count as usize])
+    }
+}
+
+impl IsInitialized for MultisigConfigV1 {
+    fn is_initialized(&self) -> bool {
+        self.version == Self::VERSION
+            && self.signer_count as usize <= MAX_SIGNERS
+            && self.threshold > 0
+    }
+}
+
+/// Multisig config
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C, packed)]
 pub struct MultisigConfig {
+    /// Account type, always [`MultisigConfig::ACCOUNT_TYPE`]
+    pub account_type: u8,
     /// Version for upgrade compatibility
     pub version: u8,
     /// Number of required approvals
@@ -23,30 +109,74 @@ pub struct MultisigConfig {
     pub signer_count: u8,
     /// Signers list
     pub signers: [Pubkey; MAX_SIGNERS],
+    /// Kind of each signer slot, see [`MemberKind`]
+    pub member_kinds: [u8; MAX_SIGNERS],
+    /// Configuration generation, bumped on every change of the settings and
+    /// part of the digest signed for off-chain and nested approvals, so that
+    /// approvals cannot be replayed against another configuration
+    pub generation: u64,
+    /// Key allowed to pause the multisig in an emergency, default if unset
+    pub guardian: Pubkey,
+    /// Is the multisig paused: 0 - false, 1 - true. While paused only
+    /// unpause proposals can be submitted, approved and executed.
+    pub paused: u8,
+    /// Number of proposals ever submitted, the next proposal index
+    pub proposal_count: u64,
+    /// Keys of the proposals not executed nor closed yet, default for a
+    /// free slot
+    pub active_proposals: [Pubkey; MAX_ACTIVE_PROPOSALS],
 }
 
 impl MultisigConfig {
     /// Current multisig version. Does not need to be aligned with proposal.
-    pub const CURRENT_VERSION: u8 = 1;
+    pub const CURRENT_VERSION: u8 = 2;
 
-    pub const SIZE: usize = 1 + 1 + 1 + 32 * MAX_SIGNERS;
+    /// Account type in the first byte of multisig configs, apart from the
+    /// proposal one and from those of the program embedding the multisig
+    pub const ACCOUNT_TYPE: u8 = 0x91;
+
+    pub const SIZE: usize =
+        1 + 1 + 1 + 1 + 32 * MAX_SIGNERS + MAX_SIGNERS + 8 + 32 + 1 + 8 + 32 * MAX_ACTIVE_PROPOSALS;
 
     pub fn new(threshold: u8, signers_in: &[Pubkey]) -> Result<Self, ProgramError> {
-        let signer_count = signers_in.len() as u8;
+        let members = signers_in
+            .iter()
+            .map(|key| (*key, MemberKind::Key))
+            .collect::<Vec<_>>();
+        Self::new_with_members(threshold, &members)
+    }
+
+    /// builds a config where some slots may be held by other multisigs
+    pub fn new_with_members(
+        threshold: u8,
+        members: &[(Pubkey, MemberKind)],
+    ) -> Result<Self, ProgramError> {
+        let signer_count = members.len() as u8;
 
-        if signer_count as usize > MAX_SIGNERS {
+        if members.len() > MAX_SIGNERS {
             msg!("Invalid signer length: must be less than MAX_SIGNERS");
-            return Err(ProgramError::InvalidArgument);
+            return Err(MultisigError::TooManyMembers.into());
         }
 
         let mut signers = [Pubkey::default(); MAX_SIGNERS];
-        signers[..signers_in.len()].copy_from_slice(signers_in);
+        let mut member_kinds = [MemberKind::Key as u8; MAX_SIGNERS];
+        for (i, (key, kind)) in members.iter().enumerate() {
+            signers[i] = *key;
+            member_kinds[i] = *kind as u8;
+        }
 
         Ok(Self {
+            account_type: Self::ACCOUNT_TYPE,
             version: MultisigConfig::CURRENT_VERSION,
             threshold,
             signer_count,
             signers,
+            member_kinds,
+            generation: 0,
+            guardian: Pubkey::default(),
+            paused: 0,
+            proposal_count: 0,
+            active_proposals: [Pubkey::default(); MAX_ACTIVE_PROPOSALS],
         })
     }
 
@@ -55,7 +185,126 @@ impl MultisigConfig {
         self.signers[..self.signer_count as usize].contains(key)
     }
 
-    /// derive config from its account info
+    /// position of the given key among the configured signers
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers[..self.signer_count as usize]
+            .iter()
+            .position(|k| k == key)
+    }
+
+    /// checks if the key is a keypair member, the only kind able to sign
+    pub fn is_key_member(&self, key: &Pubkey) -> bool {
+        self.signer_index(key)
+            .is_some_and(|i| self.member_kinds[i] == MemberKind::Key as u8)
+    }
+
+    /// signer slot holding an Ethereum address, zero-padded to 32 bytes
+    pub fn ethereum_slot(address: &[u8; ETH_ADDRESS_LENGTH]) -> Pubkey {
+        let mut slot = [0u8; 32];
+        slot[..ETH_ADDRESS_LENGTH].copy_from_slice(address);
+        Pubkey::new_from_array(slot)
+    }
+
+    /// position of the given Ethereum address among the configured signers
+    pub fn ethereum_signer_index(&self, address: &[u8; ETH_ADDRESS_LENGTH]) -> Option<usize> {
+        let slot = Self::ethereum_slot(address);
+        self.signers[..self.signer_count as usize]
+            .iter()
+            .zip(self.member_kinds)
+            .position(|(k, kind)| *k == slot && kind == MemberKind::Ethereum as u8)
+    }
+
+    /// kind of the member sitting at `index`
+    pub fn member_kind(&self, index: usize) -> Result<MemberKind, ProgramError> {
+        MemberKind::try_from(self.member_kinds[index])
+    }
+
+    /// is the multisig paused by its guardian
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    /// records a change of the settings, voiding the approvals signed for
+    /// the previous generation
+    pub fn bump_generation(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    /// keys of the proposals still open, for clients to list pending proposals
+    pub fn active_proposals(&self) -> impl Iterator<Item = &Pubkey> {
+        self.active_proposals
+            .iter()
+            .filter(|key| **key != Pubkey::default())
+    }
+
+    /// is the proposal registered as open
+    pub fn is_active_proposal(&self, proposal_key: &Pubkey) -> bool {
+        self.active_proposals().any(|key| key == proposal_key)
+    }
+
+    /// registers a new proposal in a free slot and returns its index
+    pub fn register_proposal(&mut self, proposal_key: &Pubkey) -> Result<u64, ProgramError> {
+        if self.is_active_proposal(proposal_key) {
+            msg!("Proposal is already registered");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        let slot = self
+            .active_proposals
+            .iter_mut()
+            .find(|key| **key == Pubkey::default())
+            .ok_or_else(|| {
+                msg!("Too many active proposals, execute or close some first");
+                MultisigError::TooManyActiveProposals
+            })?;
+        *slot = *proposal_key;
+
+        self.next_proposal_index()
+    }
+
+    /// hands out the next proposal index, never reused
+    pub fn next_proposal_index(&mut self) -> Result<u64, ProgramError> {
+        let index = self.proposal_count;
+        self.proposal_count = index
+            .checked_add(1)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(index)
+    }
+
+    /// frees the slot of an executed or closed proposal
+    pub fn release_proposal(&mut self, proposal_key: &Pubkey) {
+        for key in self.active_proposals.iter_mut() {
+            if key == proposal_key {
+                *key = Pubkey::default();
+            }
+        }
+    }
+
+    /// checks that `guardian_info` is the configured guardian and signed
+    pub fn check_guardian(&self, guardian_info: &AccountInfo) -> ProgramResult {
+        if self.guardian == Pubkey::default() || self.guardian != *guardian_info.key {
+            msg!("Incorrect multisig guardian provided");
+            return Err(MultisigError::IncorrectGuardian.into());
+        }
+        if !guardian_info.is_signer {
+            msg!("Multisig guardian signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    /// writes the config back into its account
+    pub fn store(&self, account_info: &AccountInfo) -> ProgramResult {
+        let mut data = account_info.try_borrow_mut_data()?;
+        if data.len() < Self::SIZE {
+            msg!("Account data is smaller than Config data");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        data[..Self::SIZE].copy_from_slice(bytemuck::bytes_of(self));
+        Ok(())
+    }
+
+    /// derive config from its account info, rejecting accounts which are not
+    /// an initialized multisig config
     pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
         let data = account_info.try_borrow_data()?;
         let self_size = Self::SIZE;
@@ -69,6 +318,10 @@ impl MultisigConfig {
             msg!("Invalid config deserialization: {:?}", e);
             ProgramError::InvalidArgument
         })?;
+        if !deserialized.is_initialized() {
+            msg!("Account is not an initialized multisig config");
+            return Err(ProgramError::UninitializedAccount);
+        }
 
         Ok(deserialized)
     }
@@ -87,8 +340,9 @@ impl MultisigConfig {
                 continue;
             }
 
-            // Must be a configured signer and have signed the transaction
-            if self.is_signer(key) && signer_info.is_signer {
+            // Must be a configured keypair signer and have signed the transaction.
+            // Nested multisigs never sign, they approve through proposals.
+            if self.is_key_member(key) && signer_info.is_signer {
                 unique_signer_keys.insert(key);
                 approved_count += 1;
             }
@@ -105,6 +359,9 @@ impl MultisigConfig {
 impl IsInitialized for MultisigConfig {
     fn is_initialized(&self) -> bool {
         let max = MAX_SIGNERS as u8;
-        self.version == Self::CURRENT_VERSION && self.signer_count <= max && self.threshold > 0
+        self.account_type == Self::ACCOUNT_TYPE
+            && self.version == Self::CURRENT_VERSION
+            && self.signer_count <= max
+            && self.threshold > 0
     }
 }
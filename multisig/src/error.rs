@@ -0,0 +1,162 @@
+//! Error types
+
+#[allow(deprecated)]
+use {
+    num_derive::FromPrimitive,
+    num_traits::FromPrimitive,
+    solana_decode_error::DecodeError,
+    solana_msg::msg,
+    solana_program_error::{PrintProgramError, ProgramError},
+    thiserror::Error,
+};
+
+/// Errors that may be returned by the multisig.
+///
+/// Codes start at [`MultisigError::FIRST_CODE`] so that they never collide
+/// with the errors of the program embedding the multisig.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum MultisigError {
+    /// Account must be writable
+    #[error("Account must be writable")]
+    AccountNotWritable = 100,
+
+    /// Multisig is paused
+    #[error("Multisig is paused")]
+    Paused,
+
+    /// Multisig is not paused
+    #[error("Multisig is not paused")]
+    NotPaused,
+
+    /// Incorrect multisig guardian provided
+    #[error("Incorrect multisig guardian provided")]
+    IncorrectGuardian,
+
+    /// Too many members for a multisig
+    #[error("Too many members for a multisig")]
+    TooManyMembers,
+
+    /// Too many active proposals, execute or close some first
+    #[error("Too many active proposals, execute or close some first")]
+    TooManyActiveProposals,
+
+    /// Instruction tag is reserved by the multisig
+    #[error("Instruction tag is reserved by the multisig")]
+    ReservedInstructionTag,
+
+    /// Proposal payload is empty
+    #[error("Proposal payload is empty")]
+    EmptyPayload,
+
+    /// Proposal description is too long
+    #[error("Proposal description is too long")]
+    DescriptionTooLong,
+
+    /// Proposal account is too small for the proposal metadata
+    #[error("Proposal account is too small for the proposal metadata")]
+    ProposalTooSmall,
+
+    /// Data does not fit the proposal account
+    #[error("Data does not fit the proposal account")]
+    OutOfBounds,
+
+    /// Proposal is not a staged proposal
+    #[error("Proposal is not a staged proposal")]
+    NotStaged,
+
+    /// Staged proposals can only be changed by their proposer
+    #[error("Staged proposals can only be changed by their proposer")]
+    NotProposer,
+
+    /// Proposal payload is not finalized yet
+    #[error("Proposal payload is not finalized yet")]
+    ProposalStaging,
+
+    /// Proposal was already executed
+    #[error("Proposal was already executed")]
+    ProposalExecuted,
+
+    /// Proposal description does not match the proposal
+    #[error("Proposal description does not match the proposal")]
+    DescriptionMismatch,
+
+    /// Proposal payload does not match the approved hash
+    #[error("Proposal payload does not match the approved hash")]
+    HashMismatch,
+
+    /// Proposal belongs to a different multisig
+    #[error("Proposal belongs to a different multisig")]
+    WrongMultisig,
+
+    /// Proposal targets a different client account
+    #[error("Proposal targets a different client account")]
+    WrongClientAccount,
+
+    /// Signer is not a member of the multisig
+    #[error("Signer is not a member of the multisig")]
+    NotAMember,
+
+    /// Member kind cannot approve this way
+    #[error("Member kind cannot approve this way")]
+    WrongMemberKind,
+
+    /// Signer already approved
+    #[error("Signer already approved")]
+    AlreadyApproved,
+
+    /// No new member signatures over the proposal digest
+    #[error("No new member signatures over the proposal digest")]
+    NoNewApprovals,
+
+    /// Signature cannot be verified
+    #[error("Signature cannot be verified")]
+    InvalidSignature,
+
+    /// A multisig cannot approve its own proposals through nesting
+    #[error("A multisig cannot approve its own proposals through nesting")]
+    NestedSelfApproval,
+
+    /// Nested multisig cycle detected
+    #[error("Nested multisig cycle detected")]
+    NestedCycle,
+
+    /// Nested approval does not approve this proposal
+    #[error("Nested approval does not approve this proposal")]
+    NestedApprovalMismatch,
+
+    /// Parent proposal is not open for approvals
+    #[error("Parent proposal is not open for approvals")]
+    ProposalNotOpen,
+
+    /// Proposal cannot be closed into itself
+    #[error("Proposal cannot be closed into itself")]
+    CloseIntoSelf,
+
+    /// Account is already at the current version
+    #[error("Account is already at the current version")]
+    AlreadyCurrentVersion,
+}
+impl MultisigError {
+    /// Code of the first multisig error
+    pub const FIRST_CODE: u32 = MultisigError::AccountNotWritable as u32;
+}
+impl From<MultisigError> for ProgramError {
+    fn from(e: MultisigError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+#[allow(deprecated)]
+impl<T> DecodeError<T> for MultisigError {
+    fn type_of() -> &'static str {
+        "Multisig Error"
+    }
+}
+#[allow(deprecated)]
+impl PrintProgramError for MultisigError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!(&self.to_string());
+    }
+}
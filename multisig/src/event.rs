@@ -0,0 +1,318 @@
+//! Multisig events
+//!
+//! Events are logged with `sol_log_data` as a single field:
+//! `[schema version][tag][fields]`, integers little-endian. Multisig tags
+//! start at [`MultisigEvent::FIRST_TAG`] so that they never collide with the
+//! events of the program embedding the multisig.
+use {solana_msg::msg, solana_program_error::ProgramError, solana_pubkey::Pubkey};
+
+/// Version of the event wire format, bumped on any incompatible change
+pub const EVENT_SCHEMA_VERSION: u8 = 1;
+
+/// log `event` as `Program data:`, the form indexers pick up
+pub fn emit(event: &[u8]) {
+    #[cfg(target_os = "solana")]
+    unsafe {
+        let fields: [&[u8]; 1] = [event];
+        solana_msg::syscalls::sol_log_data(fields.as_ptr() as *const u8, fields.len() as u64);
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    msg!("Program data: {:?}", event);
+}
+
+/// reads a public key at the start of `input`, returning it with the rest
+pub fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+    let (key, rest) = input
+        .split_first_chunk::<32>()
+        .ok_or(ProgramError::InvalidArgument)?;
+    Ok((Pubkey::new_from_array(*key), rest))
+}
+
+/// reads a little-endian `u64` at the start of `input`, returning it with the rest
+pub fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    let (value, rest) = input
+        .split_first_chunk::<8>()
+        .ok_or(ProgramError::InvalidArgument)?;
+    Ok((u64::from_le_bytes(*value), rest))
+}
+
+/// Event logged on every proposal or multisig mutation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MultisigEvent {
+    /// A proposal was registered with its multisig
+    ProposalCreated {
+        /// Proposal account
+        proposal: Pubkey,
+        /// Multisig controlling the proposal
+        multisig: Pubkey,
+        /// Account which submitted the proposal
+        proposer: Pubkey,
+        /// Position of the proposal among all proposals of its multisig
+        index: u64,
+    },
+    /// The payload of a proposal buffer was uploaded and the proposal opened
+    /// for approvals
+    ProposalFinalized {
+        /// Proposal account
+        proposal: Pubkey,
+    },
+    /// Members approved a proposal
+    ProposalApproved {
+        /// Proposal account
+        proposal: Pubkey,
+        /// Bitmask of all approvals so far
+        signer_approvals: u16,
+    },
+    /// A proposal reached its threshold and was executed
+    ProposalExecuted {
+        /// Proposal account
+        proposal: Pubkey,
+    },
+    /// A proposal was closed
+    ProposalClosed {
+        /// Proposal account
+        proposal: Pubkey,
+    },
+    /// The guardian paused a multisig
+    MultisigPaused {
+        /// Multisig config account
+        multisig: Pubkey,
+    },
+    /// An unpause proposal lifted the pause of its multisig
+    MultisigUnpaused {
+        /// Multisig config account
+        multisig: Pubkey,
+    },
+    /// A multisig config was upgraded to the current layout
+    MultisigMigrated {
+        /// Multisig config account
+        multisig: Pubkey,
+        /// Version of the config after the upgrade
+        version: u8,
+    },
+    /// A proposal was upgraded to the current layout
+    ProposalMigrated {
+        /// Proposal account
+        proposal: Pubkey,
+        /// Version of the proposal after the upgrade
+        version: u8,
+    },
+    /// A proposal set the guardian of its multisig
+    GuardianSet {
+        /// Multisig config account
+        multisig: Pubkey,
+        /// New guardian, the default key if the multisig has none anymore
+        guardian: Pubkey,
+    },
+}
+
+impl MultisigEvent {
+    /// Tag of the first multisig event
+    pub const FIRST_TAG: u8 = 128;
+
+    /// pack the event into a byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![EVENT_SCHEMA_VERSION];
+        match self {
+            Self::ProposalCreated {
+                proposal,
+                multisig,
+                proposer,
+                index,
+            } => {
+                buf.push(Self::FIRST_TAG);
+                buf.extend_from_slice(proposal.as_ref());
+                buf.extend_from_slice(multisig.as_ref());
+                buf.extend_from_slice(proposer.as_ref());
+                buf.extend_from_slice(&index.to_le_bytes());
+            }
+            Self::ProposalFinalized { proposal } => {
+                buf.push(Self::FIRST_TAG + 1);
+                buf.extend_from_slice(proposal.as_ref());
+            }
+            Self::ProposalApproved {
+                proposal,
+                signer_approvals,
+            } => {
+                buf.push(Self::FIRST_TAG + 2);
+                buf.extend_from_slice(proposal.as_ref());
+                buf.extend_from_slice(&signer_approvals.to_le_bytes());
+            }
+            Self::ProposalExecuted { proposal } => {
+                buf.push(Self::FIRST_TAG + 3);
+                buf.extend_from_slice(proposal.as_ref());
+            }
+            Self::ProposalClosed { proposal } => {
+                buf.push(Self::FIRST_TAG + 4);
+                buf.extend_from_slice(proposal.as_ref());
+            }
+            Self::MultisigPaused { multisig } => {
+                buf.push(Self::FIRST_TAG + 5);
+                buf.extend_from_slice(multisig.as_ref());
+            }
+            Self::MultisigUnpaused { multisig } => {
+                buf.push(Self::FIRST_TAG + 6);
+                buf.extend_from_slice(multisig.as_ref());
+            }
+            Self::MultisigMigrated { multisig, version } => {
+                buf.push(Self::FIRST_TAG + 7);
+                buf.extend_from_slice(multisig.as_ref());
+                buf.push(*version);
+            }
+            Self::ProposalMigrated { proposal, version } => {
+                buf.push(Self::FIRST_TAG + 8);
+                buf.extend_from_slice(proposal.as_ref());
+                buf.push(*version);
+            }
+            Self::GuardianSet { multisig, guardian } => {
+                buf.push(Self::FIRST_TAG + 9);
+                buf.extend_from_slice(multisig.as_ref());
+                buf.extend_from_slice(guardian.as_ref());
+            }
+        }
+        buf
+    }
+
+    /// unpack an event logged by [`Self::emit`]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&version, rest) = input.split_first().ok_or(ProgramError::InvalidArgument)?;
+        if version != EVENT_SCHEMA_VERSION {
+            msg!("Unsupported event schema version {}", version);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (&tag, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+        let (key, rest) = unpack_pubkey(rest)?;
+        let (event, rest) = match tag.wrapping_sub(Self::FIRST_TAG) {
+            0 => {
+                let (multisig, rest) = unpack_pubkey(rest)?;
+                let (proposer, rest) = unpack_pubkey(rest)?;
+                let (index, rest) = unpack_u64(rest)?;
+                let event = Self::ProposalCreated {
+                    proposal: key,
+                    multisig,
+                    proposer,
+                    index,
+                };
+                (event, rest)
+            }
+            1 => (Self::ProposalFinalized { proposal: key }, rest),
+            2 => {
+                let (signer_approvals, rest) = rest
+                    .split_first_chunk::<2>()
+                    .ok_or(ProgramError::InvalidArgument)?;
+                let event = Self::ProposalApproved {
+                    proposal: key,
+                    signer_approvals: u16::from_le_bytes(*signer_approvals),
+                };
+                (event, rest)
+            }
+            3 => (Self::ProposalExecuted { proposal: key }, rest),
+            4 => (Self::ProposalClosed { proposal: key }, rest),
+            5 => (Self::MultisigPaused { multisig: key }, rest),
+            6 => (Self::MultisigUnpaused { multisig: key }, rest),
+            7 => {
+                let (&version, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                let event = Self::MultisigMigrated {
+                    multisig: key,
+                    version,
+                };
+                (event, rest)
+            }
+            8 => {
+                let (&version, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                let event = Self::ProposalMigrated {
+                    proposal: key,
+                    version,
+                };
+                (event, rest)
+            }
+            9 => {
+                let (guardian, rest) = unpack_pubkey(rest)?;
+                let event = Self::GuardianSet {
+                    multisig: key,
+                    guardian,
+                };
+                (event, rest)
+            }
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+        if !rest.is_empty() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(event)
+    }
+
+    /// log the event
+    pub fn emit(&self) {
+        emit(&self.pack());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unpack_events() {
+        let proposal = Pubkey::new_from_array([1; 32]);
+        let multisig = Pubkey::new_from_array([2; 32]);
+        let events = [
+            MultisigEvent::ProposalCreated {
+                proposal,
+                multisig,
+                proposer: Pubkey::new_from_array([3; 32]),
+                index: 7,
+            },
+            MultisigEvent::ProposalFinalized { proposal },
+            MultisigEvent::ProposalApproved {
+                proposal,
+                signer_approvals: 0b101,
+            },
+            MultisigEvent::ProposalExecuted { proposal },
+            MultisigEvent::ProposalClosed { proposal },
+            MultisigEvent::MultisigPaused { multisig },
+            MultisigEvent::MultisigUnpaused { multisig },
+            MultisigEvent::MultisigMigrated {
+                multisig,
+                version: 2,
+            },
+            MultisigEvent::ProposalMigrated {
+                proposal,
+                version: 3,
+            },
+            MultisigEvent::GuardianSet {
+                multisig,
+                guardian: Pubkey::new_from_array([4; 32]),
+            },
+        ];
+        for event in events {
+            let packed = event.pack();
+            assert_eq!(packed[0], EVENT_SCHEMA_VERSION);
+            assert!(packed[1] >= MultisigEvent::FIRST_TAG);
+            assert_eq!(MultisigEvent::unpack(&packed).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn unpack_invalid_events() {
+        let packed = MultisigEvent::ProposalExecuted {
+            proposal: Pubkey::new_from_array([1; 32]),
+        }
+        .pack();
+
+        let mut wrong_version = packed.clone();
+        wrong_version[0] = EVENT_SCHEMA_VERSION + 1;
+        assert!(MultisigEvent::unpack(&wrong_version).is_err());
+
+        let mut trailing = packed.clone();
+        trailing.push(0);
+        assert!(MultisigEvent::unpack(&trailing).is_err());
+
+        assert!(MultisigEvent::unpack(&packed[..packed.len() - 1]).is_err());
+
+        let mut record_tag = packed;
+        record_tag[1] = 0;
+        assert!(MultisigEvent::unpack(&record_tag).is_err());
+    }
+}
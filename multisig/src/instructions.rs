@@ -1,12 +1,84 @@
 //! Multisig instructions
-use crate::{config::MultisigConfig, proposal::Proposal};
+use crate::{
+    config::{MemberKind, MultisigConfig, MultisigConfigV1, ETH_ADDRESS_LENGTH},
+    error::MultisigError,
+    event::MultisigEvent,
+    proposal::{Proposal, ProposalV1},
+};
 use solana_account_info::{next_account_info, AccountInfo};
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_keccak_hasher::hash;
 use solana_msg::msg;
 use solana_program_error::{ProgramError, ProgramResult};
+use solana_program_pack::IsInitialized;
 use solana_pubkey::Pubkey;
+use solana_sdk_ids::{ed25519_program, system_program};
+use solana_secp256k1_recover::{secp256k1_recover, SECP256K1_SIGNATURE_LENGTH};
 
-/// initializes multisig write proposal.
-pub fn initialize_multisig_write(accounts: &[AccountInfo<'_>], instr_data: &[u8]) -> ProgramResult {
+/// registers the proposal with its multisig, then writes the proposal
+/// metadata followed by its description and payload into the proposal account.
+/// The proposer must be a keypair member of the multisig, and the proposal
+/// account a newly created one, all zeroes.
+fn write_proposal(
+    proposer: &AccountInfo,
+    proposal_account: &AccountInfo,
+    multisig_account: &AccountInfo,
+    mut multisig: MultisigConfig,
+    mut proposal: Proposal,
+    description: &[u8],
+    instr_data: &[u8],
+) -> ProgramResult {
+    // members only, so that outsiders cannot fill the active proposal slots
+    if !multisig.is_key_member(proposer.key) {
+        msg!("Proposals can only be submitted by a keypair member");
+        return Err(MultisigError::NotAMember.into());
+    }
+
+    // Proposal account should be large as metadata (struct data) + description + actual data
+    let mut proposal_data = proposal_account.try_borrow_mut_data()?;
+    if proposal_data.len() < Proposal::SIZE {
+        msg!("Proposal account is too small for proposal metadata");
+        return Err(MultisigError::ProposalTooSmall.into());
+    }
+    // anything else owned by the program, a record or a used proposal, holds data
+    if proposal_data.iter().any(|byte| *byte != 0) {
+        msg!("Proposal account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (meta, body) = proposal_data.split_at_mut(Proposal::SIZE);
+
+    if description.len() + instr_data.len() > body.len() {
+        msg!("Proposal account is too small for the payload");
+        return Err(MultisigError::OutOfBounds.into());
+    }
+
+    proposal.proposer = *proposer.key;
+    proposal.index = multisig.register_proposal(proposal_account.key)?;
+    multisig.store(multisig_account)?;
+
+    meta.copy_from_slice(bytemuck::bytes_of(&proposal));
+
+    let (description_slot, payload) = body.split_at_mut(description.len());
+    description_slot.copy_from_slice(description);
+    payload[..instr_data.len()].copy_from_slice(instr_data);
+
+    MultisigEvent::ProposalCreated {
+        proposal: *proposal_account.key,
+        multisig: *multisig_account.key,
+        proposer: *proposer.key,
+        index: proposal.index,
+    }
+    .emit();
+    Ok(())
+}
+
+/// initializes multisig write proposal. The optional `description` is stored
+/// alongside the payload for approvers to review.
+pub fn initialize_multisig_write(
+    accounts: &[AccountInfo<'_>],
+    instr_data: &[u8],
+    description: &[u8],
+) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let payer = next_account_info(account_info_iter)?; // signer
 
@@ -20,27 +92,34 @@ pub fn initialize_multisig_write(accounts: &[AccountInfo<'_>], instr_data: &[u8]
 
     if !proposal_account.is_writable {
         msg!("Proposal account must be writable");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(MultisigError::AccountNotWritable.into());
     }
 
     if !client_account.is_writable {
         msg!("Client account must be writable");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(MultisigError::AccountNotWritable.into());
     }
 
-    let multisig_account = next_account_info(account_info_iter)?; // read-only
+    let multisig_account = next_account_info(account_info_iter)?; // writable
 
-    if multisig_account.is_writable {
-        msg!("Multisig account should be read-only");
-        return Err(ProgramError::InvalidAccountData);
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
     }
 
     // Validate multisig config
-    let _multisig = MultisigConfig::from_account_info(multisig_account)?;
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if multisig.is_paused() {
+        msg!("Multisig is paused");
+        return Err(MultisigError::Paused.into());
+    }
+
+    let instruction_tag = instr_data.first().ok_or(MultisigError::EmptyPayload)?;
 
-    let instruction_tag = instr_data
-        .first()
-        .ok_or(ProgramError::InvalidInstructionData)?;
+    if Proposal::is_reserved_tag(*instruction_tag) {
+        msg!("Instruction tag is reserved by the multisig");
+        return Err(MultisigError::ReservedInstructionTag.into());
+    }
 
     // Create the proposal metadata
     let proposal = Proposal::new(
@@ -48,109 +127,1070 @@ pub fn initialize_multisig_write(accounts: &[AccountInfo<'_>], instr_data: &[u8]
         *client_account.key,
         *multisig_account.key,
         instr_data,
-    );
+    )
+    .with_description(description)?;
 
-    // Proposal account should be large as metadata (struct data) + actual data
-    let mut proposal_data = proposal_account.try_borrow_mut_data()?;
-    let (meta, payload) = proposal_data.split_at_mut(Proposal::SIZE);
+    write_proposal(
+        payer,
+        proposal_account,
+        multisig_account,
+        multisig,
+        proposal,
+        description,
+        instr_data,
+    )
+}
 
-    let new_meta_bytes = bytemuck::bytes_of(&proposal);
+/// initializes a proposal buffer, for payloads too large to be submitted in
+/// a single transaction. The proposer then uploads the payload in chunks
+/// with [`process_write_proposal_buffer`] and opens the proposal for
+/// approvals with [`process_finalize_proposal_buffer`].
+pub fn initialize_proposal_buffer(
+    accounts: &[AccountInfo<'_>],
+    description: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?; // signer
 
-    if new_meta_bytes.len() > meta.len() {
-        return Err(ProgramError::InvalidInstructionData);
+    if !payer.is_signer {
+        msg!("Payer is not a signer!");
+        return Err(ProgramError::MissingRequiredSignature);
     }
-    meta[..].copy_from_slice(new_meta_bytes);
 
-    if instr_data.len() > payload.len() {
-        return Err(ProgramError::InvalidInstructionData);
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let client_account = next_account_info(account_info_iter)?; // read-only
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    if !proposal_account.is_writable {
+        msg!("Proposal account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
     }
-    payload[..instr_data.len()].copy_from_slice(instr_data);
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if multisig.is_paused() {
+        msg!("Multisig is paused");
+        return Err(MultisigError::Paused.into());
+    }
+
+    let mut proposal = Proposal::new(0, *client_account.key, *multisig_account.key, &[])
+        .with_description(description)?;
+    proposal.staging = 1;
+
+    write_proposal(
+        payer,
+        proposal_account,
+        multisig_account,
+        multisig,
+        proposal,
+        description,
+        &[],
+    )
+}
+
+/// loads a staged proposal, checking that `proposer` submitted it and signed
+fn load_staged_proposal(
+    proposer: &AccountInfo,
+    proposal_data: &[u8],
+) -> Result<Proposal, ProgramError> {
+    if proposal_data.len() < Proposal::SIZE {
+        msg!("Account data is smaller than Proposal data");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let proposal = *bytemuck::try_from_bytes::<Proposal>(&proposal_data[..Proposal::SIZE])
+        .map_err(|e| {
+            msg!("Invalid proposal deserialization: {:?}", e);
+            ProgramError::InvalidArgument
+        })?;
+
+    if !proposal.is_initialized() || !proposal.is_staging() {
+        msg!("Proposal is not a staged proposal");
+        return Err(MultisigError::NotStaged.into());
+    }
+
+    if proposal.proposer != *proposer.key {
+        msg!("Staged proposals can only be changed by their proposer");
+        return Err(MultisigError::NotProposer.into());
+    }
+    if !proposer.is_signer {
+        msg!("Proposer signature missing");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    Ok(proposal)
+}
+
+/// writes a chunk of the payload of a staged proposal at `offset`
+pub fn process_write_proposal_buffer(
+    accounts: &[AccountInfo<'_>],
+    offset: u64,
+    chunk: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let proposer = next_account_info(account_info_iter)?; // signer
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+
+    let mut data = proposal_account.try_borrow_mut_data()?;
+    let proposal = load_staged_proposal(proposer, &data)?;
+
+    let start = usize::try_from(offset)
+        .ok()
+        .and_then(|offset| offset.checked_add(Proposal::SIZE + proposal.description_len as usize))
+        .ok_or(ProgramError::InvalidArgument)?;
+    let end = start
+        .checked_add(chunk.len())
+        .ok_or(ProgramError::InvalidArgument)?;
+    if end > data.len() {
+        msg!("Proposal account is too small for the chunk");
+        return Err(MultisigError::OutOfBounds.into());
+    }
+    data[start..end].copy_from_slice(chunk);
 
     Ok(())
 }
 
-/// Process approve. If threshold is reached, the write is executed.
-pub fn process_approve_proposal<F>(accounts: &[AccountInfo<'_>], pda_handler: F) -> ProgramResult
-where
-    F: Fn(&[u8], &AccountInfo, &Pubkey) -> ProgramResult,
-{
+/// finalizes a staged proposal: hashes the uploaded payload and opens the
+/// proposal for approvals. The payload can no longer change afterwards.
+pub fn process_finalize_proposal_buffer(accounts: &[AccountInfo<'_>]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let signer = next_account_info(account_info_iter)?; // signer
+    let proposer = next_account_info(account_info_iter)?; // signer
     let proposal_account = next_account_info(account_info_iter)?; // writable
-    let client_account = next_account_info(account_info_iter)?; // writable
-    let multisig_account = next_account_info(account_info_iter)?; // read-only
 
     let mut data = proposal_account.try_borrow_mut_data()?;
+    let mut proposal = load_staged_proposal(proposer, &data)?;
 
-    if data.len() < Proposal::SIZE {
-        msg!(
-            "meta data is too small! data len: {}, proposal len: {}",
-            data.len(),
-            Proposal::SIZE
-        );
-        return Err(ProgramError::InvalidAccountData);
+    let (meta, body) = data.split_at_mut(Proposal::SIZE);
+    let (_, payload) = proposal.split_body(body)?;
+
+    let instruction_tag = *payload.first().ok_or_else(|| {
+        msg!("Proposal payload is empty");
+        MultisigError::EmptyPayload
+    })?;
+
+    if Proposal::is_reserved_tag(instruction_tag) {
+        msg!("Instruction tag is reserved by the multisig");
+        return Err(MultisigError::ReservedInstructionTag.into());
+    }
+
+    proposal.instruction_tag = instruction_tag;
+    proposal.finalize_staged(payload);
+    meta.copy_from_slice(bytemuck::bytes_of(&proposal));
+
+    MultisigEvent::ProposalFinalized {
+        proposal: *proposal_account.key,
+    }
+    .emit();
+    Ok(())
+}
+
+/// initializes a nested approval: a proposal of a child multisig which,
+/// once executed, counts as the child's approval of a parent proposal.
+///
+/// The payload of the child proposal is the parent's
+/// [`Proposal::approval_digest`], so the approval is bound to the exact
+/// contents the child members voted on, to that very parent proposal (a
+/// proposal later re-created at the same address has a new index) and to
+/// the current generation of the parent multisig config.
+pub fn initialize_nested_approval(accounts: &[AccountInfo<'_>]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?; // signer
+
+    if !payer.is_signer {
+        msg!("Payer is not a signer!");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let parent_proposal_account = next_account_info(account_info_iter)?; // read-only
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+    let parent_multisig_account = next_account_info(account_info_iter)?; // read-only
+
+    if !proposal_account.is_writable {
+        msg!("Proposal account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    if parent_proposal_account.owner != proposal_account.owner
+        || parent_multisig_account.owner != proposal_account.owner
+    {
+        msg!("Parent proposal or multisig is owned by a different program");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let parent_proposal = Proposal::from_account_info(parent_proposal_account)?;
+    if parent_proposal.is_staging() || parent_proposal.is_executed() {
+        msg!("Parent proposal is not open for approvals");
+        return Err(MultisigError::ProposalNotOpen.into());
+    }
+
+    if parent_proposal.multisig_key == *multisig_account.key {
+        msg!("A multisig cannot approve its own proposal through nesting");
+        return Err(MultisigError::NestedSelfApproval.into());
+    }
+
+    if parent_proposal.multisig_key != *parent_multisig_account.key {
+        msg!("Multisignature accounts don't match!");
+        return Err(MultisigError::WrongMultisig.into());
+    }
+    let parent_multisig = MultisigConfig::from_account_info(parent_multisig_account)?;
+
+    // Validate multisig config
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if multisig.is_paused() {
+        msg!("Multisig is paused");
+        return Err(MultisigError::Paused.into());
+    }
+
+    let digest =
+        parent_proposal.approval_digest(parent_proposal_account.key, parent_multisig.generation);
+    let proposal = Proposal::new(
+        Proposal::NESTED_APPROVAL_TAG,
+        *parent_proposal_account.key,
+        *multisig_account.key,
+        &digest,
+    );
+
+    write_proposal(
+        payer,
+        proposal_account,
+        multisig_account,
+        multisig,
+        proposal,
+        &[],
+        &digest,
+    )
+}
+
+/// pauses a multisig. Only its guardian may do so; lifting the pause takes
+/// an unpause proposal reaching the regular threshold.
+pub fn process_pause(accounts: &[AccountInfo<'_>]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let guardian = next_account_info(account_info_iter)?; // signer
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    let mut multisig = MultisigConfig::from_account_info(multisig_account)?;
+    multisig.check_guardian(guardian)?;
+
+    if multisig.is_paused() {
+        msg!("Multisig is already paused");
+        return Ok(());
+    }
+    multisig.paused = 1;
+    multisig.bump_generation();
+    multisig.store(multisig_account)?;
+
+    MultisigEvent::MultisigPaused {
+        multisig: *multisig_account.key,
+    }
+    .emit();
+    Ok(())
+}
+
+/// initializes a proposal to unpause a paused multisig. The multisig config
+/// is both the target and the controlling account of such a proposal.
+pub fn initialize_unpause_proposal(accounts: &[AccountInfo<'_>]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?; // signer
+
+    if !payer.is_signer {
+        msg!("Payer is not a signer!");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    if !proposal_account.is_writable {
+        msg!("Proposal account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if !multisig.is_paused() {
+        msg!("Multisig is not paused");
+        return Err(MultisigError::NotPaused.into());
+    }
+
+    let instr_data = [Proposal::UNPAUSE_TAG];
+    let proposal = Proposal::new(
+        Proposal::UNPAUSE_TAG,
+        *multisig_account.key,
+        *multisig_account.key,
+        &instr_data,
+    );
+
+    write_proposal(
+        payer,
+        proposal_account,
+        multisig_account,
+        multisig,
+        proposal,
+        &[],
+        &instr_data,
+    )
+}
+
+/// initializes a proposal to set the guardian of a multisig, the default
+/// key leaving the multisig without a guardian. The multisig config is both
+/// the target and the controlling account of such a proposal.
+pub fn initialize_set_guardian_proposal(
+    accounts: &[AccountInfo<'_>],
+    guardian: &Pubkey,
+    description: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer = next_account_info(account_info_iter)?; // signer
+
+    if !payer.is_signer {
+        msg!("Payer is not a signer!");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    if !proposal_account.is_writable {
+        msg!("Proposal account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if multisig.is_paused() {
+        msg!("Multisig is paused");
+        return Err(MultisigError::Paused.into());
+    }
+
+    let mut instr_data = vec![Proposal::SET_GUARDIAN_TAG];
+    instr_data.extend_from_slice(guardian.as_ref());
+    let proposal = Proposal::new(
+        Proposal::SET_GUARDIAN_TAG,
+        *multisig_account.key,
+        *multisig_account.key,
+        &instr_data,
+    )
+    .with_description(description)?;
+
+    write_proposal(
+        payer,
+        proposal_account,
+        multisig_account,
+        multisig,
+        proposal,
+        description,
+        &instr_data,
+    )
+}
+
+/// closes a proposal, draining its lamports to the receiver and removing it
+/// from the active proposals of its multisig. Any keypair member may close a
+/// proposal, whether it was executed or is still open.
+pub fn process_close_proposal(accounts: &[AccountInfo<'_>]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let member = next_account_info(account_info_iter)?; // signer
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+    let receiver = next_account_info(account_info_iter)?; // writable
+
+    if proposal_account.owner != multisig_account.owner {
+        msg!("Proposal is owned by a different program");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if proposal_account.key == receiver.key {
+        msg!("Cannot close a proposal into itself");
+        return Err(MultisigError::CloseIntoSelf.into());
+    }
+
+    let proposal = Proposal::from_account_info(proposal_account)?;
+    if proposal.multisig_key != *multisig_account.key {
+        msg!("Multisignature accounts don't match!");
+        return Err(MultisigError::WrongMultisig.into());
+    }
+
+    let mut multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if !multisig.is_key_member(member.key) {
+        msg!("Proposals can only be closed by a keypair member");
+        return Err(MultisigError::NotAMember.into());
+    }
+    if !member.is_signer {
+        msg!("Member signature missing");
+        return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let (meta, payload) = data.split_at_mut(Proposal::SIZE);
+    multisig.release_proposal(proposal_account.key);
+    multisig.store(multisig_account)?;
+
+    let receiver_lamports = receiver.lamports();
+    let proposal_lamports = proposal_account.lamports();
+    **proposal_account.try_borrow_mut_lamports()? = 0;
+    **receiver.try_borrow_mut_lamports()? = receiver_lamports
+        .checked_add(proposal_lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // handed back to the system program, so a refund later in the same
+    // transaction cannot bring the proposal back
+    proposal_account.try_borrow_mut_data()?.fill(0);
+    proposal_account.resize(0)?;
+    proposal_account.assign(&system_program::id());
+
+    MultisigEvent::ProposalClosed {
+        proposal: *proposal_account.key,
+    }
+    .emit();
+    Ok(())
+}
+
+/// checks that an executed child proposal approves `parent_proposal`, at
+/// the current `generation` of the parent multisig config, on behalf of the
+/// child multisig sitting in one of the parent's slots.
+///
+/// Approvals only ever look one level down and never recurse, so a cycle
+/// cannot loop; direct cycles (the child listing the parent as a member,
+/// or a multisig nesting itself) are still rejected so that nesting stays
+/// a strict hierarchy.
+fn verify_nested_approval(
+    child_multisig_account: &AccountInfo,
+    child_proposal_account: &AccountInfo,
+    parent_multisig_account: &AccountInfo,
+    parent_proposal_key: &Pubkey,
+    parent_proposal: &Proposal,
+    generation: u64,
+) -> ProgramResult {
+    if child_multisig_account.owner != parent_multisig_account.owner
+        || child_proposal_account.owner != parent_multisig_account.owner
+    {
+        msg!("Nested multisig accounts are owned by a different program");
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if child_multisig_account.key == parent_multisig_account.key {
+        msg!("A multisig cannot be a member of itself");
+        return Err(MultisigError::NestedSelfApproval.into());
+    }
+
+    let child_multisig = MultisigConfig::from_account_info(child_multisig_account)?;
+    if child_multisig.is_signer(parent_multisig_account.key) {
+        msg!("Nested multisig cycle detected");
+        return Err(MultisigError::NestedCycle.into());
+    }
 
-    let mut proposal: Proposal = *bytemuck::try_from_bytes::<Proposal>(&meta[..Proposal::SIZE])
+    let child_proposal = Proposal::from_account_info(child_proposal_account)?;
+    if !child_proposal.is_nested_approval() || !child_proposal.is_executed() {
+        msg!("Child proposal is not an executed nested approval");
+        return Err(MultisigError::NestedApprovalMismatch.into());
+    }
+
+    if child_proposal.multisig_key != *child_multisig_account.key
+        || child_proposal.client_account != *parent_proposal_key
+    {
+        msg!("Child proposal does not approve this proposal");
+        return Err(MultisigError::NestedApprovalMismatch.into());
+    }
+
+    if !child_proposal.is_instruction_data_correct(
+        &parent_proposal.approval_digest(parent_proposal_key, generation),
+    ) {
+        msg!("Child proposal approved a different proposal");
+        return Err(MultisigError::NestedApprovalMismatch.into());
+    }
+
+    Ok(())
+}
+
+/// loads the metadata of a proposal open for approvals and checks it
+/// against the accounts provided alongside it. Returns the metadata and the
+/// payload found in `body`, the account data following the metadata.
+fn load_open_proposal<'a>(
+    meta: &[u8],
+    body: &'a [u8],
+    client_account: &AccountInfo,
+    multisig_account: &AccountInfo,
+) -> Result<(Proposal, &'a [u8]), ProgramError> {
+    let proposal: Proposal = *bytemuck::try_from_bytes::<Proposal>(&meta[..Proposal::SIZE])
         .map_err(|e| {
             msg!("Invalid proposal deserialization: {:?}", e);
             ProgramError::InvalidArgument
         })?;
 
+    if !proposal.is_initialized() {
+        msg!("Proposal is not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
     if proposal.is_executed() {
         msg!("Proposal was already executed!");
-        return Err(ProgramError::InvalidAccountData); // Already executed
+        return Err(MultisigError::ProposalExecuted.into());
+    }
+
+    if proposal.is_staging() {
+        msg!("Proposal payload is not finalized yet");
+        return Err(MultisigError::ProposalStaging.into());
+    }
+
+    let (description, payload) = proposal.split_body(body)?;
+
+    if !proposal.is_description_correct(description) {
+        msg!("Invalid proposal description!");
+        return Err(MultisigError::DescriptionMismatch.into());
     }
 
     if !proposal.is_instruction_data_correct(payload) {
         msg!("Invalid approving instruction data!");
-        return Err(ProgramError::InvalidAccountData);
+        return Err(MultisigError::HashMismatch.into());
     }
 
     if multisig_account.key != &proposal.multisig_key {
         msg!("Multisignature accounts don't match!");
-        return Err(ProgramError::InvalidArgument);
+        return Err(MultisigError::WrongMultisig.into());
+    }
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
     }
 
     if client_account.key != &proposal.client_account {
         msg!("Client accounts don't match!");
-        return Err(ProgramError::InvalidArgument);
+        return Err(MultisigError::WrongClientAccount.into());
+    }
+
+    Ok((proposal, payload))
+}
+
+/// while a multisig is paused, only its unpause proposals may move forward
+fn check_not_paused(multisig: &MultisigConfig, proposal: &Proposal) -> ProgramResult {
+    if multisig.is_paused() && !proposal.is_unpause() {
+        msg!("Multisig is paused");
+        return Err(MultisigError::Paused.into());
+    }
+    Ok(())
+}
+
+/// executes the proposal if the threshold is reached, releasing its slot in
+/// the multisig, then stores the updated metadata back into the proposal account
+#[allow(clippy::too_many_arguments)]
+fn execute_if_ready<F>(
+    mut proposal: Proposal,
+    proposal_key: &Pubkey,
+    mut multisig: MultisigConfig,
+    meta: &mut [u8],
+    payload: &[u8],
+    client_account: &AccountInfo,
+    multisig_account: &AccountInfo,
+    pda_handler: F,
+) -> ProgramResult
+where
+    F: Fn(&[u8], &AccountInfo, &Pubkey) -> ProgramResult,
+{
+    MultisigEvent::ProposalApproved {
+        proposal: *proposal_key,
+        signer_approvals: proposal.signer_approvals,
+    }
+    .emit();
+
+    // If threshold reached, execute
+    if proposal.is_ready_to_execute(multisig.threshold) {
+        msg!("Threshold reached, executing instruction");
+
+        if proposal.is_unpause() {
+            multisig.paused = 0;
+            multisig.bump_generation();
+            msg!("Multisig unpaused");
+            MultisigEvent::MultisigUnpaused {
+                multisig: *multisig_account.key,
+            }
+            .emit();
+        } else if proposal.is_set_guardian() {
+            let guardian = payload
+                .get(1..33)
+                .and_then(|key| <[u8; 32]>::try_from(key).ok())
+                .map(Pubkey::new_from_array)
+                .ok_or(ProgramError::InvalidAccountData)?;
+            multisig.guardian = guardian;
+            multisig.bump_generation();
+            msg!("Multisig guardian set to {}", guardian);
+            MultisigEvent::GuardianSet {
+                multisig: *multisig_account.key,
+                guardian,
+            }
+            .emit();
+        } else if !proposal.is_nested_approval() {
+            // A nested approval has nothing to execute: the parent multisig
+            // consumes it when the child's slot approves the parent proposal.
+            pda_handler(payload, client_account, multisig_account.key)?;
+        }
+
+        proposal.set_executed();
+        multisig.release_proposal(proposal_key);
+        multisig.store(multisig_account)?;
+        MultisigEvent::ProposalExecuted {
+            proposal: *proposal_key,
+        }
+        .emit();
+    } else {
+        msg!("Updating proposal, threshold not yet reached.");
     }
 
+    meta[..Proposal::SIZE].copy_from_slice(bytemuck::bytes_of(&proposal));
+
+    Ok(())
+}
+
+/// Process approve. If threshold is reached, the write is executed.
+///
+/// When the approver slot holds a nested multisig, the approver account is the
+/// child multisig config (not a signer) and the child's executed nested approval
+/// proposal must follow the multisig account.
+pub fn process_approve_proposal<F>(accounts: &[AccountInfo<'_>], pda_handler: F) -> ProgramResult
+where
+    F: Fn(&[u8], &AccountInfo, &Pubkey) -> ProgramResult,
+{
+    let account_info_iter = &mut accounts.iter();
+    let signer = next_account_info(account_info_iter)?; // signer
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let client_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    let mut data = proposal_account.try_borrow_mut_data()?;
+
+    if data.len() < Proposal::SIZE {
+        msg!(
+            "meta data is too small! data len: {}, proposal len: {}",
+            data.len(),
+            Proposal::SIZE
+        );
+        return Err(MultisigError::ProposalTooSmall.into());
+    }
+
+    let (meta, body) = data.split_at_mut(Proposal::SIZE);
+
+    let (mut proposal, payload) = load_open_proposal(meta, body, client_account, multisig_account)?;
+
     // Load multisig config
     let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    check_not_paused(&multisig, &proposal)?;
 
     // Determine signer index
     let signer_index = multisig
-        .signers
-        .iter()
-        .position(|k| k == signer.key)
-        .ok_or(ProgramError::MissingRequiredSignature)?;
+        .signer_index(signer.key)
+        .ok_or(MultisigError::NotAMember)?;
 
-    if !signer.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    match multisig.member_kind(signer_index)? {
+        MemberKind::Key => {
+            if !signer.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+        MemberKind::Multisig => {
+            let child_proposal_account = next_account_info(account_info_iter)?; // read-only
+            verify_nested_approval(
+                signer,
+                child_proposal_account,
+                multisig_account,
+                proposal_account.key,
+                &proposal,
+                multisig.generation,
+            )?;
+        }
+        MemberKind::Ethereum => {
+            msg!("Ethereum members approve with a secp256k1 signature");
+            return Err(MultisigError::WrongMemberKind.into());
+        }
     }
 
     let updated = proposal.approve(signer_index);
     if !updated {
         msg!("Signer already approved");
-        return Err(ProgramError::InvalidArgument);
+        return Err(MultisigError::AlreadyApproved.into());
     }
 
-    // If threshold reached, execute
-    if proposal.is_ready_to_execute(multisig.threshold) {
-        msg!("Threshold reached, executing instruction");
+    execute_if_ready(
+        proposal,
+        proposal_account.key,
+        multisig,
+        meta,
+        payload,
+        client_account,
+        multisig_account,
+        pda_handler,
+    )
+}
+
+/// Layout of the Ed25519 precompile instruction data, see
+/// `solana-ed25519-program`. Kept local to avoid pulling its off-chain
+/// signing dependencies into the program.
+mod ed25519 {
+    pub const PUBKEY_SERIALIZED_SIZE: usize = 32;
+    pub const SIGNATURE_OFFSETS_SERIALIZED_SIZE: usize = 14;
+    pub const SIGNATURE_OFFSETS_START: usize = 2;
+    /// Instruction index meaning "the Ed25519 instruction itself"
+    pub const CURRENT_INSTRUCTION: u16 = u16::MAX;
+}
 
-        pda_handler(payload, client_account, multisig_account.key)?;
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ProgramError> {
+    data.get(offset..offset + 2)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
 
-        proposal.set_executed();
-    } else {
-        msg!("Updating proposal, threshold not yet reached.");
+/// collects `(public key, message)` pairs of every signature verified by an
+/// Ed25519 precompile instruction. Offsets pointing into other instructions
+/// are rejected so the pair is always read from where the runtime verified it.
+fn ed25519_signed_messages(
+    instruction_index: u16,
+    data: &[u8],
+) -> Result<Vec<(Pubkey, &[u8])>, ProgramError> {
+    let num_signatures = *data.first().ok_or(ProgramError::InvalidInstructionData)? as usize;
+    let mut signed = Vec::with_capacity(num_signatures);
+
+    for i in 0..num_signatures {
+        let start =
+            ed25519::SIGNATURE_OFFSETS_START + i * ed25519::SIGNATURE_OFFSETS_SERIALIZED_SIZE;
+        let signature_instruction_index = read_u16(data, start + 2)?;
+        let public_key_offset = read_u16(data, start + 4)? as usize;
+        let public_key_instruction_index = read_u16(data, start + 6)?;
+        let message_data_offset = read_u16(data, start + 8)? as usize;
+        let message_data_size = read_u16(data, start + 10)? as usize;
+        let message_instruction_index = read_u16(data, start + 12)?;
+
+        let is_local =
+            |index: u16| index == ed25519::CURRENT_INSTRUCTION || index == instruction_index;
+        if !is_local(signature_instruction_index)
+            || !is_local(public_key_instruction_index)
+            || !is_local(message_instruction_index)
+        {
+            msg!("Ed25519 signature data must live in its own instruction");
+            return Err(MultisigError::InvalidSignature.into());
+        }
+
+        let public_key = data
+            .get(public_key_offset..public_key_offset + ed25519::PUBKEY_SERIALIZED_SIZE)
+            .map(Pubkey::try_from)
+            .and_then(Result::ok)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let message = data
+            .get(message_data_offset..message_data_offset + message_data_size)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+
+        signed.push((public_key, message));
     }
 
-    meta[..Proposal::SIZE].copy_from_slice(bytemuck::bytes_of(&proposal));
+    Ok(signed)
+}
+
+/// Process approvals signed off-chain. Every Ed25519 precompile instruction
+/// preceding this one in the transaction is inspected, and each member key
+/// that signed the proposal's [`Proposal::approval_digest`] is recorded as an
+/// approval. If threshold is reached, the write is executed.
+pub fn process_approve_with_signatures<F>(
+    accounts: &[AccountInfo<'_>],
+    pda_handler: F,
+) -> ProgramResult
+where
+    F: Fn(&[u8], &AccountInfo, &Pubkey) -> ProgramResult,
+{
+    let account_info_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let client_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+    let instructions_sysvar = next_account_info(account_info_iter)?; // read-only
+
+    let mut data = proposal_account.try_borrow_mut_data()?;
+
+    if data.len() < Proposal::SIZE {
+        msg!(
+            "meta data is too small! data len: {}, proposal len: {}",
+            data.len(),
+            Proposal::SIZE
+        );
+        return Err(MultisigError::ProposalTooSmall.into());
+    }
+
+    let (meta, body) = data.split_at_mut(Proposal::SIZE);
+
+    let (mut proposal, payload) = load_open_proposal(meta, body, client_account, multisig_account)?;
+
+    // Load multisig config
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    check_not_paused(&multisig, &proposal)?;
+    let digest = proposal.approval_digest(proposal_account.key, multisig.generation);
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let mut new_approvals = 0usize;
+
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+        if instruction.program_id != ed25519_program::ID {
+            continue;
+        }
+
+        for (public_key, message) in ed25519_signed_messages(index, &instruction.data)? {
+            if message != digest {
+                continue;
+            }
+            let Some(signer_index) = multisig.signer_index(&public_key) else {
+                msg!("Skipping signature of non-member {}", public_key);
+                continue;
+            };
+            if multisig.member_kind(signer_index)? != MemberKind::Key {
+                msg!("Member {} cannot sign with Ed25519", public_key);
+                return Err(MultisigError::WrongMemberKind.into());
+            }
+            if proposal.approve(signer_index) {
+                new_approvals += 1;
+            }
+        }
+    }
+
+    if new_approvals == 0 {
+        msg!("No new member signatures over the proposal digest");
+        return Err(MultisigError::NoNewApprovals.into());
+    }
+    msg!("Recorded {} off-chain approvals", new_approvals);
+
+    execute_if_ready(
+        proposal,
+        proposal_account.key,
+        multisig,
+        meta,
+        payload,
+        client_account,
+        multisig_account,
+        pda_handler,
+    )
+}
+
+/// Order of the secp256k1 curve divided by two. Signatures with a larger `s`
+/// are rejected so that every approval has a single valid encoding.
+const SECP256K1_HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa0,
+];
+
+/// recovers the Ethereum address that produced `signature` over `digest`
+fn recover_eth_address(
+    digest: &[u8; 32],
+    recovery_id: u8,
+    signature: &[u8; SECP256K1_SIGNATURE_LENGTH],
+) -> Result<[u8; ETH_ADDRESS_LENGTH], ProgramError> {
+    // Ethereum tooling encodes the recovery id as `v = 27 + id`
+    let recovery_id = match recovery_id {
+        0 | 1 => recovery_id,
+        27 | 28 => recovery_id - 27,
+        _ => {
+            msg!("Invalid secp256k1 recovery id {}", recovery_id);
+            return Err(MultisigError::InvalidSignature.into());
+        }
+    };
+
+    if signature[32..] > SECP256K1_HALF_ORDER[..] {
+        msg!("secp256k1 signature is not normalized to low s");
+        return Err(MultisigError::InvalidSignature.into());
+    }
+
+    let public_key = secp256k1_recover(digest, recovery_id, signature).map_err(|e| {
+        msg!("secp256k1 recovery failed: {:?}", e);
+        MultisigError::InvalidSignature
+    })?;
+
+    let mut address = [0u8; ETH_ADDRESS_LENGTH];
+    address.copy_from_slice(&hash(&public_key.to_bytes()).0[32 - ETH_ADDRESS_LENGTH..]);
+    Ok(address)
+}
+
+/// Process an approval of an Ethereum member, who signed the proposal's
+/// [`Proposal::eth_approval_digest`] with an Ethereum wallet. If threshold is
+/// reached, the write is executed.
+pub fn process_approve_with_eth_signature<F>(
+    accounts: &[AccountInfo<'_>],
+    recovery_id: u8,
+    signature: &[u8; SECP256K1_SIGNATURE_LENGTH],
+    pda_handler: F,
+) -> ProgramResult
+where
+    F: Fn(&[u8], &AccountInfo, &Pubkey) -> ProgramResult,
+{
+    let account_info_iter = &mut accounts.iter();
+    let proposal_account = next_account_info(account_info_iter)?; // writable
+    let client_account = next_account_info(account_info_iter)?; // writable
+    let multisig_account = next_account_info(account_info_iter)?; // writable
+
+    let mut data = proposal_account.try_borrow_mut_data()?;
+
+    if data.len() < Proposal::SIZE {
+        msg!(
+            "meta data is too small! data len: {}, proposal len: {}",
+            data.len(),
+            Proposal::SIZE
+        );
+        return Err(MultisigError::ProposalTooSmall.into());
+    }
 
+    let (meta, body) = data.split_at_mut(Proposal::SIZE);
+
+    let (mut proposal, payload) = load_open_proposal(meta, body, client_account, multisig_account)?;
+
+    // Load multisig config
+    let multisig = MultisigConfig::from_account_info(multisig_account)?;
+    check_not_paused(&multisig, &proposal)?;
+    let digest = proposal.eth_approval_digest(proposal_account.key, multisig.generation);
+
+    let address = recover_eth_address(&digest, recovery_id, signature)?;
+    let signer_index = multisig.ethereum_signer_index(&address).ok_or_else(|| {
+        msg!("Signature does not belong to an Ethereum member");
+        MultisigError::NotAMember
+    })?;
+
+    let updated = proposal.approve(signer_index);
+    if !updated {
+        msg!("Signer already approved");
+        return Err(MultisigError::AlreadyApproved.into());
+    }
+
+    execute_if_ready(
+        proposal,
+        proposal_account.key,
+        multisig,
+        meta,
+        payload,
+        client_account,
+        multisig_account,
+        pda_handler,
+    )
+}
+
+/// upgrades a v1 multisig config to the current layout, approved by a
+/// threshold of its signers. Members keep their slots, as keypair members,
+/// so that the approvals of v1 proposals keep their meaning. The config
+/// grows, `resize` reallocating the account and funding its rent.
+pub fn process_migrate_multisig<F>(
+    multisig_account: &AccountInfo,
+    signer_infos: &[AccountInfo],
+    resize: F,
+) -> ProgramResult
+where
+    F: FnOnce(usize) -> ProgramResult,
+{
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    let legacy = {
+        let data = multisig_account.try_borrow_data()?;
+        if data.first() == Some(&MultisigConfig::ACCOUNT_TYPE) {
+            msg!("Multisig config is already at the current version");
+            return Err(MultisigError::AlreadyCurrentVersion.into());
+        }
+        MultisigConfigV1::unpack(&data)?
+    };
+    let mut multisig = legacy.migrate()?;
+    multisig.verify_signatures(signer_infos)?;
+    multisig.bump_generation();
+
+    if multisig_account.data_len() < MultisigConfig::SIZE {
+        resize(MultisigConfig::SIZE)?;
+    }
+    multisig.store(multisig_account)?;
+
+    MultisigEvent::MultisigMigrated {
+        multisig: *multisig_account.key,
+        version: MultisigConfig::CURRENT_VERSION,
+    }
+    .emit();
+    Ok(())
+}
+
+/// upgrades a v1 proposal to the current layout, shifting its payload behind
+/// the larger metadata. Its multisig must be migrated first: the proposal
+/// gets the next index and, if still open, an active proposal slot, the
+/// migrating member being recorded as its proposer. `resize` reallocates the
+/// account and funds its rent.
+pub fn process_migrate_proposal<F>(
+    member: &AccountInfo,
+    proposal_account: &AccountInfo,
+    multisig_account: &AccountInfo,
+    resize: F,
+) -> ProgramResult
+where
+    F: FnOnce(usize) -> ProgramResult,
+{
+    if !proposal_account.is_writable {
+        msg!("Proposal account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    if !multisig_account.is_writable {
+        msg!("Multisig account must be writable");
+        return Err(MultisigError::AccountNotWritable.into());
+    }
+
+    let mut multisig = MultisigConfig::from_account_info(multisig_account)?;
+    if !multisig.is_key_member(member.key) {
+        msg!("Proposals can only be migrated by a keypair member");
+        return Err(MultisigError::NotAMember.into());
+    }
+    if !member.is_signer {
+        msg!("Member signature missing");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let legacy = {
+        let data = proposal_account.try_borrow_data()?;
+        if data.first() == Some(&Proposal::ACCOUNT_TYPE) {
+            msg!("Proposal is already at the current version");
+            return Err(MultisigError::AlreadyCurrentVersion.into());
+        }
+        ProposalV1::unpack(&data)?
+    };
+    if legacy.multisig_key != *multisig_account.key {
+        msg!("Multisignature accounts don't match!");
+        return Err(MultisigError::WrongMultisig.into());
+    }
+
+    let mut proposal = legacy.migrate();
+    proposal.proposer = *member.key;
+    proposal.index = if proposal.is_executed() {
+        multisig.next_proposal_index()?
+    } else {
+        multisig.register_proposal(proposal_account.key)?
+    };
+    multisig.store(multisig_account)?;
+
+    let old_len = proposal_account.data_len();
+    let new_len = old_len
+        .checked_add(Proposal::SIZE - ProposalV1::SIZE)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    resize(new_len)?;
+
+    let mut data = proposal_account.try_borrow_mut_data()?;
+    data.copy_within(ProposalV1::SIZE..old_len, Proposal::SIZE);
+    data[..Proposal::SIZE].copy_from_slice(bytemuck::bytes_of(&proposal));
+
+    MultisigEvent::ProposalMigrated {
+        proposal: *proposal_account.key,
+        version: Proposal::CURRENT_VERSION,
+    }
+    .emit();
     Ok(())
 }
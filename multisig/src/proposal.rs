@@ -1,17 +1,84 @@
 //! Multisig proposal data
 use {
+    crate::error::MultisigError,
     bytemuck::{Pod, Zeroable},
-    solana_keccak_hasher::hash,
+    solana_account_info::AccountInfo,
+    solana_keccak_hasher::{hash, hashv},
+    solana_msg::msg,
+    solana_program_error::ProgramError,
     solana_program_pack::IsInitialized,
     solana_pubkey::Pubkey,
 };
 
+/// Proposal metadata as laid out before account types, descriptions and the
+/// proposal registry, the payload following right after it. Such proposals
+/// are upgraded to [`Proposal`] with
+/// [`crate::instructions::process_migrate_proposal`].
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ProposalV1 {
+    /// Struct version, always [`ProposalV1::VERSION`]
+    pub version: u8,
+    /// Has the proposal been executed: 0 - false, 1 - true
+    pub executed: u8,
+    /// Single-digit discriminator for instruction to be executed
+    pub instruction_tag: u8,
+    /// Bitmask of approvals (up to 16 signers)
+    pub signer_approvals: u16,
+    /// Account being targeted
+    pub client_account: Pubkey,
+    /// Multisig account controlling the proposal
+    pub multisig_key: Pubkey,
+    /// Data hash
+    pub hashed_data: [u8; 32],
+}
+
+impl ProposalV1 {
+    /// Version of the legacy layout
+    pub const VERSION: u8 = 1;
+
+    /// Offset in account data where the `data` payload begins
+    /// 1 + 1 + 1 + 2 + 32 + 32 + 32
+    pub const SIZE: usize = 101;
+
+    /// reads legacy proposal metadata from the start of account data
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        let proposal = data
+            .get(..Self::SIZE)
+            .and_then(|bytes| bytemuck::try_from_bytes::<Self>(bytes).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if proposal.version != Self::VERSION {
+            msg!("Account is not a v1 proposal");
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(*proposal)
+    }
+
+    /// the same proposal in the current layout, without description. The
+    /// caller registers it and records its proposer.
+    pub fn migrate(&self) -> Proposal {
+        Proposal {
+            executed: self.executed,
+            signer_approvals: self.signer_approvals,
+            hashed_data: self.hashed_data,
+            ..Proposal::new(
+                self.instruction_tag,
+                self.client_account,
+                self.multisig_key,
+                &[],
+            )
+        }
+    }
+}
+
 /// A pending instruction proposal for multisig-controlled actions
 /// Instruction data is not included, as it will be included in the account.
 /// This structure represents metadata.
 #[repr(C, packed)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 pub struct Proposal {
+    /// Account type, always [`Proposal::ACCOUNT_TYPE`]
+    pub account_type: u8,
     /// Struct version
     pub version: u8,
     /// Has the proposal been executed: 0 - false, 1 - true
@@ -26,15 +93,53 @@ pub struct Proposal {
     pub multisig_key: Pubkey,
     /// Data hash
     pub hashed_data: [u8; 32],
+    /// Length of the description stored between metadata and payload
+    pub description_len: u16,
+    /// Description hash
+    pub description_hash: [u8; 32],
+    /// Position of the proposal among all proposals of its multisig
+    pub index: u64,
+    /// Account which submitted the proposal
+    pub proposer: Pubkey,
+    /// Is the payload still being uploaded: 0 - false, 1 - true.
+    /// A staged proposal cannot be approved until its proposer finalizes it.
+    pub staging: u8,
 }
 
 impl Proposal {
     /// Current proposal version.
-    pub const CURRENT_VERSION: u8 = 1;
+    pub const CURRENT_VERSION: u8 = 3;
 
-    /// Offset in account data where `data` payload begins
-    /// 1 + 1 + 1 + 2 + 32 + 32 + 32
-    pub const SIZE: usize = 101;
+    /// Account type in the first byte of proposals, apart from the multisig
+    /// config one and from those of the program embedding the multisig
+    pub const ACCOUNT_TYPE: u8 = 0x92;
+
+    /// Offset in account data where the description begins, followed by
+    /// the `data` payload
+    /// 1 + 1 + 1 + 1 + 2 + 32 + 32 + 32 + 2 + 32 + 8 + 32 + 1
+    pub const SIZE: usize = 177;
+
+    /// Maximum length of a proposal description
+    pub const MAX_DESCRIPTION_LEN: usize = 512;
+
+    /// Domain separator of the digest signed for off-chain approvals
+    pub const APPROVAL_DIGEST_DOMAIN: &'static [u8] = b"multisig-proposal-approval";
+
+    /// Prefix Ethereum wallets put in front of a signed 32-byte message
+    pub const ETH_SIGNED_MESSAGE_PREFIX: &'static [u8] = b"\x19Ethereum Signed Message:\n32";
+
+    /// Instruction tag reserved for nested approvals, i.e. a child multisig
+    /// approving a proposal of a parent multisig. Client programs must not
+    /// use it for their own instructions.
+    pub const NESTED_APPROVAL_TAG: u8 = u8::MAX;
+
+    /// Instruction tag reserved for proposals unpausing their own multisig.
+    /// Client programs must not use it for their own instructions.
+    pub const UNPAUSE_TAG: u8 = u8::MAX - 1;
+
+    /// Instruction tag reserved for proposals setting the guardian of their
+    /// own multisig. Client programs must not use it for their own instructions.
+    pub const SET_GUARDIAN_TAG: u8 = u8::MAX - 2;
 
     pub fn new(
         instruction_tag: u8,
@@ -45,6 +150,7 @@ impl Proposal {
         let hashed_data = hash(Self::trim_trailing_zeros_slice(instr_data)).0;
 
         Self {
+            account_type: Self::ACCOUNT_TYPE,
             version: Self::CURRENT_VERSION,
             executed: 0,
             signer_approvals: 0,
@@ -52,9 +158,84 @@ impl Proposal {
             client_account,
             multisig_key,
             hashed_data,
+            description_len: 0,
+            description_hash: hash(&[]).0,
+            index: 0,
+            proposer: Pubkey::default(),
+            staging: 0,
         }
     }
 
+    /// attach a human-readable description (title, link, rationale) which
+    /// is stored in front of the payload
+    pub fn with_description(mut self, description: &[u8]) -> Result<Self, ProgramError> {
+        if description.len() > Self::MAX_DESCRIPTION_LEN {
+            msg!(
+                "Description is too long: {} > {}",
+                description.len(),
+                Self::MAX_DESCRIPTION_LEN
+            );
+            return Err(MultisigError::DescriptionTooLong.into());
+        }
+        self.description_len = description.len() as u16;
+        self.description_hash = hash(description).0;
+        Ok(self)
+    }
+
+    /// derive proposal metadata from its account info, rejecting accounts
+    /// which are not an initialized proposal
+    pub fn from_account_info(account_info: &AccountInfo) -> Result<Self, ProgramError> {
+        let data = account_info.try_borrow_data()?;
+
+        if data.len() < Self::SIZE {
+            msg!("Account data is smaller than Proposal data");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let deserialized = *bytemuck::try_from_bytes::<Self>(&data[..Self::SIZE]).map_err(|e| {
+            msg!("Invalid proposal deserialization: {:?}", e);
+            ProgramError::InvalidArgument
+        })?;
+        if !deserialized.is_initialized() {
+            msg!("Account is not an initialized proposal");
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        Ok(deserialized)
+    }
+
+    /// split proposal account data into metadata, description and payload,
+    /// so that clients can review a proposal before approving it
+    pub fn unpack_account_data(data: &[u8]) -> Result<(Self, &[u8], &[u8]), ProgramError> {
+        if data.len() < Self::SIZE {
+            msg!("Account data is smaller than Proposal data");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (meta, body) = data.split_at(Self::SIZE);
+
+        let proposal = *bytemuck::try_from_bytes::<Self>(meta).map_err(|e| {
+            msg!("Invalid proposal deserialization: {:?}", e);
+            ProgramError::InvalidArgument
+        })?;
+        let (description, payload) = proposal.split_body(body)?;
+
+        Ok((proposal, description, payload))
+    }
+
+    /// split the account data following the metadata into description and payload
+    pub fn split_body<'a>(&self, body: &'a [u8]) -> Result<(&'a [u8], &'a [u8]), ProgramError> {
+        let description_len = self.description_len as usize;
+        if description_len > Self::MAX_DESCRIPTION_LEN || description_len > body.len() {
+            msg!("Invalid proposal description length: {}", description_len);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(body.split_at(description_len))
+    }
+
+    pub fn is_description_correct(&self, description: &[u8]) -> bool {
+        self.description_hash == hash(description).0
+    }
+
     fn trim_trailing_zeros_slice(data: &[u8]) -> &[u8] {
         let mut end = data.len();
         while end > 0 && data[end - 1] == 0 {
@@ -68,6 +249,37 @@ impl Proposal {
         self.hashed_data == hashed_data
     }
 
+    /// canonical digest a member signs off-chain to approve this proposal:
+    /// the proposal account, its index with the multisig, the `generation`
+    /// of the multisig config and the payload hash. The index is never
+    /// reused, so a signature cannot approve another proposal later created
+    /// at the same address, nor can it outlive a change of the config.
+    pub fn approval_digest(&self, proposal_key: &Pubkey, generation: u64) -> [u8; 32] {
+        let index = self.index;
+        let hashed_data = self.hashed_data;
+        hashv(&[
+            Self::APPROVAL_DIGEST_DOMAIN,
+            proposal_key.as_ref(),
+            &index.to_le_bytes(),
+            &generation.to_le_bytes(),
+            &hashed_data,
+        ])
+        .0
+    }
+
+    /// digest an Ethereum member signs with `personal_sign`, i.e. the
+    /// [`Self::approval_digest`] behind the Ethereum signed message prefix.
+    /// It covers the proposal index and config generation as well, so a
+    /// signature relayed for a closed proposal is rejected by one re-created
+    /// at the same address.
+    pub fn eth_approval_digest(&self, proposal_key: &Pubkey, generation: u64) -> [u8; 32] {
+        hashv(&[
+            Self::ETH_SIGNED_MESSAGE_PREFIX,
+            &self.approval_digest(proposal_key, generation),
+        ])
+        .0
+    }
+
     /// check is this signer already approved.
     pub fn is_approved_by(&self, signer_index: usize) -> bool {
         (self.signer_approvals & (1 << signer_index)) != 0
@@ -88,6 +300,42 @@ impl Proposal {
         }
     }
 
+    /// is this a child proposal approving a parent multisig proposal
+    pub fn is_nested_approval(&self) -> bool {
+        self.instruction_tag == Self::NESTED_APPROVAL_TAG
+    }
+
+    /// is this a proposal to unpause its own multisig
+    pub fn is_unpause(&self) -> bool {
+        self.instruction_tag == Self::UNPAUSE_TAG
+    }
+
+    /// is this a proposal to set the guardian of its own multisig
+    pub fn is_set_guardian(&self) -> bool {
+        self.instruction_tag == Self::SET_GUARDIAN_TAG
+    }
+
+    /// is the tag reserved by the multisig for its own proposals
+    pub fn is_reserved_tag(instruction_tag: u8) -> bool {
+        [
+            Self::NESTED_APPROVAL_TAG,
+            Self::UNPAUSE_TAG,
+            Self::SET_GUARDIAN_TAG,
+        ]
+        .contains(&instruction_tag)
+    }
+
+    /// is the payload of this proposal still being uploaded
+    pub fn is_staging(&self) -> bool {
+        self.staging != 0
+    }
+
+    /// hash the uploaded payload of a staged proposal, opening it for approvals
+    pub fn finalize_staged(&mut self, instr_data: &[u8]) {
+        self.hashed_data = hash(Self::trim_trailing_zeros_slice(instr_data)).0;
+        self.staging = 0;
+    }
+
     /// was this proposal executed already
     pub fn is_executed(&self) -> bool {
         self.executed != 0
@@ -110,6 +358,6 @@ impl Proposal {
 
 impl IsInitialized for Proposal {
     fn is_initialized(&self) -> bool {
-        self.version == Self::CURRENT_VERSION
+        self.account_type == Self::ACCOUNT_TYPE && self.version == Self::CURRENT_VERSION
     }
 }
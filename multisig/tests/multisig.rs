@@ -1,17 +1,29 @@
-use all2all_controller::{processor::process_instruction, state::RecordData};
+use all2all_controller::{
+    error::RecordError,
+    instruction::{self, RecordInstruction, RecordInstructionTag},
+    processor::process_instruction,
+    state::{Delegation, RecordData, RecordDataV1},
+};
 use bytemuck::bytes_of;
-use multisig::{config::MultisigConfig, proposal::Proposal};
+use multisig::{
+    config::{MemberKind, MultisigConfig, MultisigConfigV1, MAX_SIGNERS},
+    error::MultisigError,
+    proposal::{Proposal, ProposalV1},
+};
+use std::mem::size_of;
 
 use {
+    solana_program_pack::IsInitialized,
     solana_program_test::*,
     solana_pubkey::Pubkey,
     solana_sdk::{
         account::Account,
-        instruction::{AccountMeta, Instruction},
+        instruction::InstructionError,
         msg,
         signature::{Keypair, Signer},
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
+    solana_system_interface::{instruction as system_instruction, program as system_program},
 };
 
 #[tokio::test]
@@ -51,8 +63,9 @@ async fn test_multisig_write_approval_execution() {
     // data to be copied to another account
 
     let record_key = Pubkey::new_unique();
-    let record_header = RecordData {
-        version: 1,
+    // a record created before the length was tracked
+    let record_header = RecordDataV1 {
+        version: RecordDataV1::VERSION,
         authority: multisig_key,
     };
     let mut record_data = vec![0u8; 100];
@@ -95,44 +108,34 @@ async fn test_multisig_write_approval_execution() {
     // === Instruction 1: Initialize Write Proposal ===
     let payload = b"hello!";
 
-    let ix = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(payer.pubkey(), true), // payer is signer & writable
-            AccountMeta::new(proposal_key, false),  // writable (assume signer if needed)
-            AccountMeta::new(record_key, false),    // writable
-            AccountMeta::new_readonly(multisig_key, false), // readonly, not signer
-        ],
-        data: {
-            let mut d = vec![5]; // instruction_tag = 5 (submit)
-            d.extend_from_slice(&0u64.to_le_bytes()); // offset = 0
-            d.extend_from_slice(&(payload.len() as u32).to_le_bytes()); // data length to write
-            d.extend_from_slice(payload); // data to write
-                                          // instr + metadata + execute_data
-            d
-        },
-    };
+    let ix = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        &[],
+    );
 
     let tx = Transaction::new_signed_with_payer(
         &[ix],
         Some(&payer.pubkey()),
-        &[&payer],
+        &[&payer, &signer1],
         recent_blockhash,
     );
     msg!("Processing first transaction!");
     banks_client.process_transaction(tx).await.unwrap();
 
     // === Instruction 2: Signer1 approves ===
-    let ix = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(signer1.pubkey(), true), // signer1: signer & writable
-            AccountMeta::new(proposal_key, false),    // writable, not signer
-            AccountMeta::new(record_key, false),      // writable, not signer
-            AccountMeta::new_readonly(multisig_key, false), // readonly, not signer
-        ],
-        data: vec![6], // instruction_tag = 6 (approve)
-    };
+    let ix = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
     let tx = Transaction::new_signed_with_payer(
         &[ix],
         Some(&payer.pubkey()),
@@ -142,16 +145,13 @@ async fn test_multisig_write_approval_execution() {
     banks_client.process_transaction(tx).await.unwrap();
 
     // === Instruction 3: Signer2 approves — triggers execution ===
-    let ix = Instruction {
-        program_id,
-        accounts: vec![
-            AccountMeta::new(signer2.pubkey(), true), // signer1: signer & writable
-            AccountMeta::new(proposal_key, false),    // writable, not signer
-            AccountMeta::new(record_key, false),      // writable, not signer
-            AccountMeta::new_readonly(multisig_key, false), // readonly, not signer
-        ],
-        data: vec![6], // instruction_tag = 6 (approve)
-    };
+    let ix = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer2.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
     let tx = Transaction::new_signed_with_payer(
         &[ix],
         Some(&payer.pubkey()),
@@ -167,7 +167,2149 @@ async fn test_multisig_write_approval_execution() {
         .unwrap()
         .expect("record account should exist");
 
-    let start_idx = RecordData::WRITABLE_START_INDEX;
+    let start_idx = RecordDataV1::WRITABLE_START_INDEX;
     let written = &record_account.data[start_idx..start_idx + payload.len()];
     assert_eq!(written, payload);
 }
+
+#[tokio::test]
+async fn test_nested_multisig_approval() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    // === Child multisig: a team-level 1 of 2 ===
+    let team_signer1 = Keypair::new();
+    let team_signer2 = Keypair::new();
+    let child_multisig_key = Pubkey::new_unique();
+    let child_config =
+        MultisigConfig::new(1, &[team_signer1.pubkey(), team_signer2.pubkey()]).unwrap();
+
+    // === Parent multisig: org-level 2 of 2, one slot is the team multisig ===
+    let org_signer = Keypair::new();
+    let parent_multisig_key = Pubkey::new_unique();
+    let parent_config = MultisigConfig::new_with_members(
+        2,
+        &[
+            (org_signer.pubkey(), MemberKind::Key),
+            (child_multisig_key, MemberKind::Multisig),
+        ],
+    )
+    .unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(parent_multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let parent_proposal_keypair = Keypair::new();
+    let parent_proposal_key = parent_proposal_keypair.pubkey();
+    let child_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (child_multisig_key, bytes_of(&child_config).to_vec()),
+        (parent_multisig_key, bytes_of(&parent_config).to_vec()),
+        (record_key, record_data.clone()),
+        (
+            parent_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+        (child_proposal_key, vec![0u8; Proposal::SIZE + 32]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Propose a write on the parent multisig ===
+    let payload = b"nested";
+    let propose_write = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &org_signer.pubkey(),
+        &parent_proposal_key,
+        &record_key,
+        &parent_multisig_key,
+        0,
+        payload,
+        &[],
+    );
+    // === Org signer approves directly ===
+    let org_approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &org_signer.pubkey(),
+        &parent_proposal_key,
+        &record_key,
+        &parent_multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write.clone(), org_approve],
+        Some(&payer.pubkey()),
+        &[&payer, &org_signer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Team proposes and reaches its own threshold to approve ===
+    let propose_nested = instruction::propose_nested_approval_with_program_id(
+        &program_id,
+        &team_signer2.pubkey(),
+        &child_proposal_key,
+        &parent_proposal_key,
+        &child_multisig_key,
+        &parent_multisig_key,
+    );
+    let team_approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &team_signer2.pubkey(),
+        &child_proposal_key,
+        &parent_proposal_key,
+        &child_multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_nested, team_approve],
+        Some(&payer.pubkey()),
+        &[&payer, &team_signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    assert_eq!(record_account.data, record_data, "parent not yet executed");
+
+    // === The team's slot approves the parent proposal, executing the write ===
+    let nested_approve = instruction::approve_proposal_as_multisig_with_program_id(
+        &program_id,
+        &child_multisig_key,
+        &child_proposal_key,
+        &parent_proposal_key,
+        &record_key,
+        &parent_multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[nested_approve],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+
+    // === The team's approval does not carry over to a proposal re-created at the same address ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_proposal_with_program_id(
+                &program_id,
+                &org_signer.pubkey(),
+                &parent_proposal_key,
+                &parent_multisig_key,
+                &payer.pubkey(),
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &parent_proposal_key,
+                1_000_000_000,
+                (Proposal::SIZE + record_data.len()) as u64,
+                &program_id,
+            ),
+            propose_write,
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &org_signer, &parent_proposal_keypair],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let nested_approve = instruction::approve_proposal_as_multisig_with_program_id(
+        &program_id,
+        &child_multisig_key,
+        &child_proposal_key,
+        &parent_proposal_key,
+        &record_key,
+        &parent_multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[nested_approve],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::NestedApprovalMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_approve_with_offchain_signatures() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let signer3 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config =
+        MultisigConfig::new(2, &[signer1.pubkey(), signer2.pubkey(), signer3.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let proposal_keypair = Keypair::new();
+    let proposal_key = proposal_keypair.pubkey();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (proposal_key, vec![0u8; Proposal::SIZE + record_data.len()]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let payload = b"batched";
+    let propose = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer3.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        &[],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose.clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer3],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Members sign the proposal digest off-chain ===
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let proposal = *bytemuck::from_bytes::<Proposal>(&proposal_account.data[..Proposal::SIZE]);
+    assert_eq!(
+        RecordInstructionTag::try_from(proposal.instruction_tag),
+        Ok(RecordInstructionTag::ProposeMultiWrite)
+    );
+    let digest = proposal.approval_digest(&proposal_key, multisig_config.generation);
+
+    let mut instructions = [&signer1, &signer2]
+        .iter()
+        .map(|signer| {
+            let signature = signer.sign_message(&digest);
+            solana_ed25519_program::new_ed25519_instruction_with_signature(
+                &digest,
+                &signature.as_ref().try_into().unwrap(),
+                &signer.pubkey().to_bytes(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let approve = instruction::approve_with_signatures_with_program_id(
+        &program_id,
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
+    instructions.push(approve);
+
+    // === Both approvals land in a single transaction, executing the write ===
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+
+    // === The signatures do not approve a proposal re-created at the same address ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_proposal_with_program_id(
+                &program_id,
+                &signer3.pubkey(),
+                &proposal_key,
+                &multisig_key,
+                &payer.pubkey(),
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &proposal_key,
+                1_000_000_000,
+                (Proposal::SIZE + record_data.len()) as u64,
+                &program_id,
+            ),
+            propose,
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer3, &proposal_keypair],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(MultisigError::NoNewApprovals as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_offchain_signatures_fail_after_config_change() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let guardian = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let mut multisig_config =
+        MultisigConfig::new(2, &[signer1.pubkey(), signer2.pubkey()]).unwrap();
+    multisig_config.guardian = guardian.pubkey();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let proposal_key = Pubkey::new_unique();
+    let unpause_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (proposal_key, vec![0u8; Proposal::SIZE + record_data.len()]),
+        (unpause_proposal_key, vec![0u8; Proposal::SIZE + 1]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let payload = b"signed before the pause";
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::propose_multi_write_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+            0,
+            payload,
+            &[],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let proposal = *bytemuck::from_bytes::<Proposal>(&proposal_account.data[..Proposal::SIZE]);
+    let approve_tx = |generation, recent_blockhash| {
+        let digest = proposal.approval_digest(&proposal_key, generation);
+        let mut instructions = [&signer1, &signer2]
+            .iter()
+            .map(|signer| {
+                let signature = signer.sign_message(&digest);
+                solana_ed25519_program::new_ed25519_instruction_with_signature(
+                    &digest,
+                    &signature.as_ref().try_into().unwrap(),
+                    &signer.pubkey().to_bytes(),
+                )
+            })
+            .collect::<Vec<_>>();
+        instructions.push(instruction::approve_with_signatures_with_program_id(
+            &program_id,
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+        ));
+        Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[&payer],
+            recent_blockhash,
+        )
+    };
+
+    // === The guardian pauses the multisig, and members lift the pause ===
+    let approvals = [&signer1, &signer2].map(|signer| {
+        instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer.pubkey(),
+            &unpause_proposal_key,
+            &multisig_key,
+            &multisig_key,
+        )
+    });
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::pause_multisig_with_program_id(
+                &program_id,
+                &guardian.pubkey(),
+                &multisig_key,
+            ),
+            instruction::propose_unpause_multisig_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &unpause_proposal_key,
+                &multisig_key,
+            ),
+            approvals[0].clone(),
+            approvals[1].clone(),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &guardian, &signer1, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert!(!multisig_config.is_paused());
+    assert_eq!({ multisig_config.generation }, 2);
+
+    // === Signatures collected for the previous config no longer count ===
+    assert_eq!(
+        banks_client
+            .process_transaction(approve_tx(0, recent_blockhash))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(MultisigError::NoNewApprovals as u32)
+        )
+    );
+
+    banks_client
+        .process_transaction(approve_tx(multisig_config.generation, recent_blockhash))
+        .await
+        .unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+}
+
+#[tokio::test]
+async fn test_approve_with_eth_signature() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    // === Ethereum member key, managed outside of Solana tooling ===
+    let eth_secret = libsecp256k1::SecretKey::parse(&[7u8; 32]).unwrap();
+    let eth_public = libsecp256k1::PublicKey::from_secret_key(&eth_secret);
+    let eth_address: [u8; 20] = solana_keccak_hasher::hash(&eth_public.serialize()[1..]).0[12..]
+        .try_into()
+        .unwrap();
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new_with_members(
+        2,
+        &[
+            (signer1.pubkey(), MemberKind::Key),
+            (
+                MultisigConfig::ethereum_slot(&eth_address),
+                MemberKind::Ethereum,
+            ),
+        ],
+    )
+    .unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let proposal_keypair = Keypair::new();
+    let proposal_key = proposal_keypair.pubkey();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (proposal_key, vec![0u8; Proposal::SIZE + record_data.len()]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Propose and approve with the Solana member ===
+    let payload = b"from eth";
+    let propose = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        &[],
+    );
+    let approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose.clone(), approve.clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Ethereum member signs the digest and anyone relays it ===
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let proposal = *bytemuck::from_bytes::<Proposal>(&proposal_account.data[..Proposal::SIZE]);
+    let digest = proposal.eth_approval_digest(&proposal_key, multisig_config.generation);
+    let (signature, recovery_id) =
+        libsecp256k1::sign(&libsecp256k1::Message::parse(&digest), &eth_secret);
+
+    let eth_approve = instruction::approve_with_eth_signature_with_program_id(
+        &program_id,
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+        recovery_id.serialize() + 27,
+        signature.serialize(),
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[eth_approve.clone()],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+
+    // === The signature does not approve a proposal re-created at the same address ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_proposal_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &proposal_key,
+                &multisig_key,
+                &payer.pubkey(),
+            ),
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &proposal_key,
+                1_000_000_000,
+                (Proposal::SIZE + record_data.len()) as u64,
+                &program_id,
+            ),
+            propose,
+            approve,
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1, &proposal_keypair],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let tx = Transaction::new_signed_with_payer(
+        &[eth_approve],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::NotAMember as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_guardian_pause_and_unpause() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let guardian = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let mut multisig_config =
+        MultisigConfig::new(2, &[signer1.pubkey(), signer2.pubkey()]).unwrap();
+    multisig_config.guardian = guardian.pubkey();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let write_proposal_key = Pubkey::new_unique();
+    let unpause_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (
+            write_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+        (unpause_proposal_key, vec![0u8; Proposal::SIZE + 1]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Guardian freezes the multisig ===
+    let pause =
+        instruction::pause_multisig_with_program_id(&program_id, &guardian.pubkey(), &multisig_key);
+    let tx = Transaction::new_signed_with_payer(
+        &[pause],
+        Some(&payer.pubkey()),
+        &[&payer, &guardian],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Submitting a write proposal is rejected while paused ===
+    let payload = b"after pause";
+    let propose_write = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &write_proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        &[],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write.clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // === Unpause needs the full threshold ===
+    let propose_unpause = instruction::propose_unpause_multisig_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &unpause_proposal_key,
+        &multisig_key,
+    );
+    // the multisig config is the target of an unpause proposal
+    let approvals = [&signer1, &signer2].map(|signer| {
+        instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer.pubkey(),
+            &unpause_proposal_key,
+            &multisig_key,
+            &multisig_key,
+        )
+    });
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_unpause, approvals[0].clone(), approvals[1].clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert!(!multisig_config.is_paused());
+
+    // === Proposals go through again ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_guardian_pause_and_unpause_record() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let guardian = Keypair::new();
+    // a config created with a keypair, which could also sign as the record authority
+    let multisig_keypair = Keypair::new();
+    let multisig_key = multisig_keypair.pubkey();
+    let mut multisig_config =
+        MultisigConfig::new(2, &[signer1.pubkey(), signer2.pubkey()]).unwrap();
+    multisig_config.guardian = guardian.pubkey();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let write_proposal_key = Pubkey::new_unique();
+    let unpause_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (
+            write_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+        (unpause_proposal_key, vec![0u8; Proposal::SIZE + 1]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Only the guardian can pause the record ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::pause_record_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &record_key,
+            &multisig_key,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::IncorrectGuardian as u32)
+        )
+    );
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::pause_record_with_program_id(
+            &program_id,
+            &guardian.pubkey(),
+            &record_key,
+            &multisig_key,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &guardian],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === A write proposal reaching the threshold cannot execute ===
+    let payload = b"after pause";
+    let write_tx = |recent_blockhash| {
+        let propose = instruction::propose_multi_write_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &write_proposal_key,
+            &record_key,
+            &multisig_key,
+            0,
+            payload,
+            &[],
+        );
+        let approvals = [&signer1, &signer2].map(|signer| {
+            instruction::approve_proposal_with_program_id(
+                &program_id,
+                &signer.pubkey(),
+                &write_proposal_key,
+                &record_key,
+                &multisig_key,
+            )
+        });
+        Transaction::new_signed_with_payer(
+            &[propose, approvals[0].clone(), approvals[1].clone()],
+            Some(&payer.pubkey()),
+            &[&payer, &signer1, &signer2],
+            recent_blockhash,
+        )
+    };
+    assert_eq!(
+        banks_client
+            .process_transaction(write_tx(recent_blockhash))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(2, InstructionError::Custom(RecordError::Paused as u32))
+    );
+
+    // === Nor can the record authority change the record directly ===
+    for instruction in [
+        instruction::freeze_with_program_id(&program_id, &record_key, &multisig_key),
+        instruction::add_writer_with_program_id(
+            &program_id,
+            &record_key,
+            &multisig_key,
+            &signer1.pubkey(),
+            &payer.pubkey(),
+        ),
+        instruction::grant_delegation_with_program_id(
+            &program_id,
+            &record_key,
+            &multisig_key,
+            &signer1.pubkey(),
+            &payer.pubkey(),
+            0,
+            8,
+            Delegation::NO_EXPIRY,
+        ),
+    ] {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[&payer, &multisig_keypair],
+            recent_blockhash,
+        );
+        assert_eq!(
+            banks_client
+                .process_transaction(tx)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::Paused as u32)
+            )
+        );
+    }
+
+    // === Unpause needs the full threshold ===
+    let propose_unpause = instruction::propose_unpause_record_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &unpause_proposal_key,
+        &record_key,
+        &multisig_key,
+        &[],
+    );
+    let approvals = [&signer1, &signer2].map(|signer| {
+        instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer.pubkey(),
+            &unpause_proposal_key,
+            &record_key,
+            &multisig_key,
+        )
+    });
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_unpause, approvals[0].clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let header = bytemuck::from_bytes::<RecordData>(
+        &record_account.data[..RecordData::WRITABLE_START_INDEX],
+    );
+    assert!(header.is_paused());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[approvals[1].clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let header = bytemuck::from_bytes::<RecordData>(
+        &record_account.data[..RecordData::WRITABLE_START_INDEX],
+    );
+    assert!(!header.is_paused());
+
+    // === Writes go through again ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(write_tx(recent_blockhash))
+        .await
+        .unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+}
+
+#[tokio::test]
+async fn test_set_guardian_through_proposal() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let guardian = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(2, &[signer1.pubkey(), signer2.pubkey()]).unwrap();
+
+    let description = b"Appoint the incident responder";
+    let proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (
+            proposal_key,
+            vec![0u8; Proposal::SIZE + description.len() + 33],
+        ),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let pause =
+        instruction::pause_multisig_with_program_id(&program_id, &guardian.pubkey(), &multisig_key);
+    let pause_tx = |recent_blockhash| {
+        Transaction::new_signed_with_payer(
+            &[pause.clone()],
+            Some(&payer.pubkey()),
+            &[&payer, &guardian],
+            recent_blockhash,
+        )
+    };
+
+    // === Without a guardian, nobody can pause the multisig ===
+    assert_eq!(
+        banks_client
+            .process_transaction(pause_tx(recent_blockhash))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::IncorrectGuardian as u32)
+        )
+    );
+
+    // === Members appoint the guardian, the multisig config being the target ===
+    let propose = instruction::propose_set_guardian_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &multisig_key,
+        &guardian.pubkey(),
+        description,
+    );
+    let approvals = [&signer1, &signer2].map(|signer| {
+        instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer.pubkey(),
+            &proposal_key,
+            &multisig_key,
+            &multisig_key,
+        )
+    });
+    let tx = Transaction::new_signed_with_payer(
+        &[propose, approvals[0].clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert_eq!({ multisig_config.guardian }, Pubkey::default());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[approvals[1].clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert_eq!({ multisig_config.guardian }, guardian.pubkey());
+
+    // === The appointed guardian can pause the multisig ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    banks_client
+        .process_transaction(pause_tx(recent_blockhash))
+        .await
+        .unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert!(multisig_config.is_paused());
+}
+
+#[tokio::test]
+async fn test_proposal_description() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let proposal_key = Pubkey::new_unique();
+    let oversized_proposal_key = Pubkey::new_unique();
+    let proposal_space = Proposal::SIZE + Proposal::MAX_DESCRIPTION_LEN + 1 + record_data.len();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (proposal_key, vec![0u8; proposal_space]),
+        (oversized_proposal_key, vec![0u8; proposal_space]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Propose a write with a description ===
+    let payload = b"v2";
+    let description = b"Bump feed format to v2, see https://example.com/rfc/7";
+    let propose_write = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        description,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Approvers can review the description before approving ===
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let (proposal, stored_description, stored_payload) =
+        Proposal::unpack_account_data(&proposal_account.data).unwrap();
+    assert_eq!(stored_description, description);
+    assert!(proposal.is_description_correct(stored_description));
+    assert!(proposal.is_instruction_data_correct(stored_payload));
+
+    // === Too long descriptions are rejected ===
+    let oversized_description = vec![b'a'; Proposal::MAX_DESCRIPTION_LEN + 1];
+    let propose_write = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &oversized_proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        payload,
+        &oversized_description,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // === Approval executes the write, not the description ===
+    let approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[approve],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+    assert_eq!(
+        record_account.data[start_idx + payload.len()..],
+        record_data[start_idx + payload.len()..]
+    );
+}
+
+#[tokio::test]
+async fn test_proposal_registry() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let executed_proposal_key = Pubkey::new_unique();
+    let closed_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (
+            executed_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+        (
+            closed_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let propose_write = |proposal_key: &Pubkey| {
+        instruction::propose_multi_write_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            proposal_key,
+            &record_key,
+            &multisig_key,
+            0,
+            b"registry",
+            &[],
+        )
+    };
+
+    // === Submitted proposals are registered with their index ===
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            propose_write(&executed_proposal_key),
+            propose_write(&closed_proposal_key),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert_eq!({ multisig_config.proposal_count }, 2);
+    let active = multisig_config
+        .active_proposals()
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(active, vec![executed_proposal_key, closed_proposal_key]);
+
+    for (index, key) in [executed_proposal_key, closed_proposal_key]
+        .iter()
+        .enumerate()
+    {
+        let proposal_account = banks_client.get_account(*key).await.unwrap().unwrap();
+        let (proposal, _, _) = Proposal::unpack_account_data(&proposal_account.data).unwrap();
+        assert_eq!({ proposal.index }, index as u64);
+    }
+
+    // === Executed proposals leave the active set ===
+    let approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &executed_proposal_key,
+        &record_key,
+        &multisig_key,
+    );
+    // === Closed proposals leave the active set and refund the receiver ===
+    let receiver = Pubkey::new_unique();
+    let close = instruction::close_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &closed_proposal_key,
+        &multisig_key,
+        &receiver,
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[approve, close],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert_eq!(multisig_config.active_proposals().count(), 0);
+    assert_eq!({ multisig_config.proposal_count }, 2);
+
+    assert!(banks_client
+        .get_account(closed_proposal_key)
+        .await
+        .unwrap()
+        .is_none());
+    let receiver_account = banks_client.get_account(receiver).await.unwrap().unwrap();
+    assert_eq!(receiver_account.lamports, 1_000_000);
+
+    // === An initialized proposal account cannot be submitted again ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_write(&executed_proposal_key)],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // === Refunding a closed proposal in the same transaction does not revive it ===
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_proposal_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &executed_proposal_key,
+                &multisig_key,
+                &receiver,
+            ),
+            system_instruction::transfer(&payer.pubkey(), &executed_proposal_key, 1_000_000),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let revived_account = banks_client
+        .get_account(executed_proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(revived_account.owner, system_program::id());
+    assert!(revived_account.data.is_empty());
+
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &executed_proposal_key,
+            &record_key,
+            &multisig_key,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+    );
+}
+
+#[tokio::test]
+async fn test_staged_proposal_buffer() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    // a record rewrite several times larger than a transaction
+    let new_record = (0..4096u32).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+    let payload = RecordInstruction::ProposeMultiWrite {
+        offset: 0,
+        data: &new_record,
+        description: &[],
+    }
+    .pack();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; size_of::<RecordData>() + new_record.len()];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let description = b"Full feed rewrite";
+    let proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data),
+        (
+            proposal_key,
+            vec![0u8; Proposal::SIZE + description.len() + payload.len()],
+        ),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let approve = instruction::approve_proposal_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &proposal_key,
+        &record_key,
+        &multisig_key,
+    );
+
+    // === Create the buffer ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::create_proposal_buffer_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+            description,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === Upload the payload in chunks ===
+    for (i, chunk) in payload.chunks(900).enumerate() {
+        let tx = Transaction::new_signed_with_payer(
+            &[instruction::write_proposal_buffer_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &proposal_key,
+                (i * 900) as u64,
+                chunk,
+            )],
+            Some(&payer.pubkey()),
+            &[&payer, &signer1],
+            recent_blockhash,
+        );
+        banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    // === Staged proposals cannot be approved ===
+    let tx = Transaction::new_signed_with_payer(
+        &[approve.clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // === Only the proposer can write to the buffer ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::write_proposal_buffer_with_program_id(
+            &program_id,
+            &payer.pubkey(),
+            &proposal_key,
+            0,
+            &[0xff],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert!(banks_client.process_transaction(tx).await.is_err());
+
+    // === Finalize, then approve executes the whole write ===
+    let recent_blockhash = banks_client
+        .get_new_latest_blockhash(&recent_blockhash)
+        .await
+        .unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::finalize_proposal_buffer_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &proposal_key,
+            ),
+            approve,
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let (proposal, stored_description, _) =
+        Proposal::unpack_account_data(&proposal_account.data).unwrap();
+    assert!(proposal.is_executed());
+    assert_eq!(stored_description, description);
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(&record_account.data[start_idx..], &new_record[..]);
+}
+
+#[tokio::test]
+async fn test_conditional_multisig_write() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let plain_proposal_key = Pubkey::new_unique();
+    let stale_proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (
+            plain_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+        (
+            stale_proposal_key,
+            vec![0u8; Proposal::SIZE + record_data.len()],
+        ),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === Both proposals are drafted against the same, empty, contents ===
+    let propose_plain = instruction::propose_multi_write_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &plain_proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        b"first",
+        &[],
+    );
+    let propose_stale = instruction::propose_multi_write_if_match_with_program_id(
+        &program_id,
+        &signer1.pubkey(),
+        &stale_proposal_key,
+        &record_key,
+        &multisig_key,
+        0,
+        RecordData::hash_contents(&[0u8; 6]),
+        b"second",
+        &[],
+    );
+    let approve = |proposal_key: Pubkey| {
+        instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+        )
+    };
+
+    let tx = Transaction::new_signed_with_payer(
+        &[propose_plain, propose_stale, approve(plain_proposal_key)],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // === The record changed since drafting, so the second one cannot execute ===
+    let tx = Transaction::new_signed_with_payer(
+        &[approve(stale_proposal_key)],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::ContentMismatch as u32)
+        )
+    );
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(&record_account.data[start_idx..start_idx + 5], b"first");
+
+    // === The executed write is attributed to the multisig ===
+    let header = RecordData::unpack(&record_account.data).unwrap();
+    assert_eq!({ header.sequence }, 1);
+    assert_eq!({ header.last_writer }, multisig_key);
+}
+
+#[tokio::test]
+async fn test_multisig_write_fail_offset_overflow() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 200];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    let proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data.clone()),
+        (proposal_key, vec![0u8; Proposal::SIZE + record_data.len()]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === An offset past the end of the address space cannot wrap around ===
+    let tx = Transaction::new_signed_with_payer(
+        &[
+            instruction::propose_multi_write_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &proposal_key,
+                &record_key,
+                &multisig_key,
+                u64::MAX - 1,
+                b"wrapped",
+                &[],
+            ),
+            instruction::approve_proposal_with_program_id(
+                &program_id,
+                &signer1.pubkey(),
+                &proposal_key,
+                &record_key,
+                &multisig_key,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, InstructionError::ArithmeticOverflow)
+    );
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    assert_eq!(record_account.data, record_data);
+}
+
+#[tokio::test]
+async fn test_multisig_proposal_fail_unchecked_accounts() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let multisig_config = MultisigConfig::new(1, &[signer1.pubkey()]).unwrap();
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 100];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    // a record large enough to be read as a multisig config
+    let fake_multisig_key = Pubkey::new_unique();
+    let fake_multisig_header = RecordData::new(signer1.pubkey(), 0);
+    let mut fake_multisig_data = vec![0u8; MultisigConfig::SIZE];
+    fake_multisig_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&fake_multisig_header));
+
+    let proposal_key = Pubkey::new_unique();
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&multisig_config).to_vec()),
+        (record_key, record_data),
+        (fake_multisig_key, fake_multisig_data.clone()),
+        (proposal_key, vec![0u8; Proposal::SIZE + 100]),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // === A record is not a multisig config ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::propose_multi_write_with_program_id(
+            &program_id,
+            &signer1.pubkey(),
+            &proposal_key,
+            &record_key,
+            &fake_multisig_key,
+            0,
+            b"forged",
+            &[],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::UninitializedAccount)
+    );
+    let fake_multisig_account = banks_client
+        .get_account(fake_multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fake_multisig_account.data, fake_multisig_data);
+
+    // === Only members can submit proposals ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::propose_multi_write_with_program_id(
+            &program_id,
+            &payer.pubkey(),
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+            0,
+            b"outsider",
+            &[],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::NotAMember as u32)
+        )
+    );
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(proposal_account.data.iter().all(|byte| *byte == 0));
+}
+
+#[tokio::test]
+async fn test_migrate_v1_multisig_and_proposal() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let signer1 = Keypair::new();
+    let signer2 = Keypair::new();
+    let multisig_key = Pubkey::new_unique();
+    let mut signers = [Pubkey::default(); MAX_SIGNERS];
+    signers[0] = signer1.pubkey();
+    signers[1] = signer2.pubkey();
+    let legacy_config = MultisigConfigV1 {
+        version: MultisigConfigV1::VERSION,
+        threshold: 2,
+        signer_count: 2,
+        signers,
+    };
+
+    let record_key = Pubkey::new_unique();
+    let record_header = RecordData::new(multisig_key, 0);
+    let mut record_data = vec![0u8; 100];
+    record_data[..size_of::<RecordData>()].copy_from_slice(bytes_of(&record_header));
+
+    // an open v1 proposal, already approved by the first signer
+    let payload = b"migrated";
+    let write = RecordInstruction::ProposeMultiWrite {
+        offset: 0,
+        data: payload,
+        description: &[],
+    }
+    .pack();
+    let legacy_proposal = ProposalV1 {
+        version: ProposalV1::VERSION,
+        executed: 0,
+        instruction_tag: RecordInstructionTag::ProposeMultiWrite.into(),
+        signer_approvals: 1,
+        client_account: record_key,
+        multisig_key,
+        hashed_data: Proposal::new(0, record_key, multisig_key, &write).hashed_data,
+    };
+    let proposal_key = Pubkey::new_unique();
+    let mut proposal_data = bytes_of(&legacy_proposal).to_vec();
+    proposal_data.extend_from_slice(&write);
+
+    for (key, data) in [
+        (multisig_key, bytes_of(&legacy_config).to_vec()),
+        (record_key, record_data),
+        (proposal_key, proposal_data),
+    ] {
+        program_test.add_account(
+            key,
+            Account {
+                lamports: 1_000_000_000,
+                data,
+                owner: program_id,
+                ..Account::default()
+            },
+        );
+    }
+
+    let (banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let migrate_proposal = instruction::migrate_proposal_with_program_id(
+        &program_id,
+        &signer2.pubkey(),
+        &proposal_key,
+        &multisig_key,
+        &payer.pubkey(),
+    );
+
+    // === Proposals wait for their multisig to be migrated ===
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_proposal.clone()],
+        Some(&payer.pubkey()),
+        &[&payer, &signer2],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidAccountData)
+    );
+
+    // === The multisig migration needs a threshold of signers ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::migrate_multisig_with_program_id(
+            &program_id,
+            &multisig_key,
+            &payer.pubkey(),
+            &[&signer1.pubkey()],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+
+    let migrate_multisig = instruction::migrate_multisig_with_program_id(
+        &program_id,
+        &multisig_key,
+        &payer.pubkey(),
+        &[&signer1.pubkey(), &signer2.pubkey()],
+    );
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_multisig.clone(), migrate_proposal],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let multisig_account = banks_client
+        .get_account(multisig_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let multisig_config =
+        *bytemuck::from_bytes::<MultisigConfig>(&multisig_account.data[..MultisigConfig::SIZE]);
+    assert!(multisig_config.is_initialized());
+    assert!(multisig_config.is_key_member(&signer1.pubkey()));
+    assert!(multisig_config.is_key_member(&signer2.pubkey()));
+    assert_eq!({ multisig_config.threshold }, 2);
+    assert_eq!(
+        multisig_config
+            .active_proposals()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![proposal_key]
+    );
+
+    let proposal_account = banks_client
+        .get_account(proposal_key)
+        .await
+        .unwrap()
+        .unwrap();
+    let (proposal, description, migrated_payload) =
+        Proposal::unpack_account_data(&proposal_account.data).unwrap();
+    assert!(proposal.is_initialized());
+    assert_eq!({ proposal.index }, 0);
+    assert_eq!({ proposal.proposer }, signer2.pubkey());
+    assert_eq!({ proposal.signer_approvals }, 1);
+    assert!(description.is_empty());
+    assert_eq!(migrated_payload, &write[..]);
+
+    // === The second approval executes the migrated proposal ===
+    let tx = Transaction::new_signed_with_payer(
+        &[instruction::approve_proposal_with_program_id(
+            &program_id,
+            &signer2.pubkey(),
+            &proposal_key,
+            &record_key,
+            &multisig_key,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &signer2],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let record_account = banks_client.get_account(record_key).await.unwrap().unwrap();
+    let start_idx = RecordData::WRITABLE_START_INDEX;
+    assert_eq!(
+        &record_account.data[start_idx..start_idx + payload.len()],
+        payload
+    );
+
+    // === Current accounts cannot be migrated again ===
+    let tx = Transaction::new_signed_with_payer(
+        &[migrate_multisig],
+        Some(&payer.pubkey()),
+        &[&payer, &signer1, &signer2],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(tx)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(MultisigError::AlreadyCurrentVersion as u32)
+        )
+    );
+}
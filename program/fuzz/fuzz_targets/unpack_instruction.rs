@@ -0,0 +1,14 @@
+#![no_main]
+
+use {all2all_controller::instruction::RecordInstruction, libfuzzer_sys::fuzz_target};
+
+fuzz_target!(|data: &[u8]| {
+    // decoding arbitrary bytes must never panic, and anything accepted must
+    // survive a round trip through the encoder
+    if let Ok(instruction) = RecordInstruction::unpack(data) {
+        assert_eq!(
+            RecordInstruction::unpack(&instruction.pack()),
+            Ok(instruction)
+        );
+    }
+});
@@ -2,18 +2,36 @@
 
 #![cfg(all(target_os = "solana", not(feature = "no-entrypoint")))]
 
+#[allow(deprecated)]
 use {
-    solana_account_info::AccountInfo, solana_program_error::ProgramResult, solana_pubkey::Pubkey,
+    crate::error::RecordError,
+    multisig::error::MultisigError,
+    solana_account_info::AccountInfo,
+    solana_program_error::{PrintProgramError, ProgramError, ProgramResult},
+    solana_pubkey::Pubkey,
     solana_security_txt::security_txt,
 };
 
 solana_program_entrypoint::entrypoint!(process_instruction);
+#[allow(deprecated)]
 fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    crate::processor::process_instruction(program_id, accounts, instruction_data)
+    if let Err(error) =
+        crate::processor::process_instruction(program_id, accounts, instruction_data)
+    {
+        // catch the error so we can print it
+        match error {
+            ProgramError::Custom(code) if code >= MultisigError::FIRST_CODE => {
+                error.print::<MultisigError>()
+            }
+            _ => error.print::<RecordError>(),
+        }
+        return Err(error);
+    }
+    Ok(())
 }
 
 security_txt! {
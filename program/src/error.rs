@@ -1,8 +1,13 @@
 //! Error types
 
+#[allow(deprecated)]
 use {
-    num_derive::FromPrimitive, solana_decode_error::DecodeError,
-    solana_program_error::ProgramError, thiserror::Error,
+    num_derive::FromPrimitive,
+    num_traits::FromPrimitive,
+    solana_decode_error::DecodeError,
+    solana_msg::msg,
+    solana_program_error::{PrintProgramError, ProgramError},
+    thiserror::Error,
 };
 
 /// Errors that may be returned by the program.
@@ -15,14 +20,104 @@ pub enum RecordError {
     /// Calculation overflow
     #[error("Calculation overflow")]
     Overflow,
+
+    /// Record contents differ from the expected ones
+    #[error("Record contents differ from the expected ones")]
+    ContentMismatch,
+
+    /// Record authority was renounced, the record cannot change anymore
+    #[error("Record authority was renounced, the record cannot change anymore")]
+    Immutable,
+
+    /// Write outside of the byte range delegated to the signer
+    #[error("Write outside of the byte range delegated to the signer")]
+    OutsideDelegatedRange,
+
+    /// Delegation is past its expiry slot
+    #[error("Delegation is past its expiry slot")]
+    DelegationExpired,
+
+    /// Data does not fit the record account
+    #[error("Data does not fit the record account")]
+    OutOfBounds,
+
+    /// Record account is too small to hold the record header
+    #[error("Record account is too small to hold the record header")]
+    RecordTooSmall,
+
+    /// Operation is not supported by the version of the record
+    #[error("Operation is not supported by the version of the record")]
+    UnsupportedVersion,
+
+    /// Record is already at the current version
+    #[error("Record is already at the current version")]
+    AlreadyCurrentVersion,
+
+    /// No authority nomination is pending
+    #[error("No authority nomination is pending")]
+    NoPendingAuthority,
+
+    /// Account cannot be closed into itself
+    #[error("Account cannot be closed into itself")]
+    CloseIntoSelf,
+
+    /// Delegated range starts after its end
+    #[error("Delegated range starts after its end")]
+    InvalidDelegatedRange,
+
+    /// Delegation does not belong to the record or the signer
+    #[error("Delegation does not belong to the record or the signer")]
+    DelegationMismatch,
+
+    /// Signer is not a listed writer of the record
+    #[error("Signer is not a listed writer of the record")]
+    NotAListedWriter,
+
+    /// Writer is already listed
+    #[error("Writer is already listed")]
+    WriterAlreadyListed,
+
+    /// Writer is not listed
+    #[error("Writer is not listed")]
+    WriterNotListed,
+
+    /// Access list is full
+    #[error("Access list is full")]
+    AccessListFull,
+
+    /// Executed proposal does not hold a record write or unpause
+    #[error("Executed proposal does not hold a record write or unpause")]
+    InvalidProposalPayload,
+
+    /// Record still has open delegations or an access list
+    #[error("Record still has open delegations or an access list")]
+    OpenGrants,
+
+    /// Record is paused
+    #[error("Record is paused")]
+    Paused,
+
+    /// Record is not paused
+    #[error("Record is not paused")]
+    NotPaused,
 }
 impl From<RecordError> for ProgramError {
     fn from(e: RecordError) -> Self {
         ProgramError::Custom(e as u32)
     }
 }
+#[allow(deprecated)]
 impl<T> DecodeError<T> for RecordError {
     fn type_of() -> &'static str {
         "Record Error"
     }
 }
+#[allow(deprecated)]
+impl PrintProgramError for RecordError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!(&self.to_string());
+    }
+}
@@ -0,0 +1,403 @@
+//! Record events
+//!
+//! Every mutation of a record logs one event with `sol_log_data`, using the
+//! wire format of the multisig events: `[schema version][tag][fields]`,
+//! integers little-endian. Record tags stay below
+//! [`MultisigEvent::FIRST_TAG`](multisig::event::MultisigEvent::FIRST_TAG).
+
+use {
+    multisig::event::{emit, unpack_pubkey, unpack_u64, EVENT_SCHEMA_VERSION},
+    solana_msg::msg,
+    solana_program_error::ProgramError,
+    solana_pubkey::Pubkey,
+};
+
+/// Event logged on every record mutation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordEvent {
+    /// A record was initialized
+    RecordInitialized {
+        /// Record account
+        record: Pubkey,
+        /// Authority of the record
+        authority: Pubkey,
+    },
+    /// Data was written to a record, one event per written segment
+    RecordWritten {
+        /// Record account
+        record: Pubkey,
+        /// Account which wrote the data
+        writer: Pubkey,
+        /// Sequence number of the record after the write
+        sequence: u64,
+        /// Offset of the data, relative to the writable start
+        offset: u64,
+        /// Length of the data
+        len: u64,
+        /// Hash of the data, usable as the expected hash of a later
+        /// conditional write
+        hash: [u8; 32],
+    },
+    /// The record authority changed, to the immutable sentinel when frozen
+    AuthorityChanged {
+        /// Record account
+        record: Pubkey,
+        /// New authority of the record
+        authority: Pubkey,
+    },
+    /// An authority was nominated
+    AuthorityNominated {
+        /// Record account
+        record: Pubkey,
+        /// Nominated authority
+        nominee: Pubkey,
+    },
+    /// The record account was reallocated
+    RecordResized {
+        /// Record account
+        record: Pubkey,
+        /// New length of the writable data
+        data_length: u64,
+    },
+    /// The record account was closed
+    RecordClosed {
+        /// Record account
+        record: Pubkey,
+    },
+    /// The record header was migrated
+    RecordMigrated {
+        /// Record account
+        record: Pubkey,
+        /// New header version
+        version: u8,
+    },
+    /// The authority delegated writes to a range of the record
+    DelegationGranted {
+        /// Record account
+        record: Pubkey,
+        /// Delegate allowed to write
+        delegate: Pubkey,
+        /// Start of the delegated range
+        start: u64,
+        /// End of the delegated range, exclusive
+        end: u64,
+        /// Last slot of the delegation, 0 if it never expires
+        expiry_slot: u64,
+    },
+    /// A delegation was revoked
+    DelegationRevoked {
+        /// Record account
+        record: Pubkey,
+        /// Delegate which lost its delegation
+        delegate: Pubkey,
+    },
+    /// A writer was added to the access list of the record
+    WriterAdded {
+        /// Record account
+        record: Pubkey,
+        /// Added writer
+        writer: Pubkey,
+    },
+    /// A writer was removed from the access list of the record
+    WriterRemoved {
+        /// Record account
+        record: Pubkey,
+        /// Removed writer
+        writer: Pubkey,
+    },
+    /// The pending authority nomination was cancelled
+    NominationCancelled {
+        /// Record account
+        record: Pubkey,
+    },
+    /// The guardian of the multisig authority paused the record
+    RecordPaused {
+        /// Record account
+        record: Pubkey,
+    },
+    /// An unpause proposal of the multisig authority lifted the pause
+    RecordUnpaused {
+        /// Record account
+        record: Pubkey,
+    },
+}
+
+impl RecordEvent {
+    /// pack the event into a byte buffer
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![EVENT_SCHEMA_VERSION];
+        match self {
+            Self::RecordInitialized { record, authority } => {
+                buf.push(0);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(authority.as_ref());
+            }
+            Self::RecordWritten {
+                record,
+                writer,
+                sequence,
+                offset,
+                len,
+                hash,
+            } => {
+                buf.push(1);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(writer.as_ref());
+                buf.extend_from_slice(&sequence.to_le_bytes());
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.extend_from_slice(&len.to_le_bytes());
+                buf.extend_from_slice(hash);
+            }
+            Self::AuthorityChanged { record, authority } => {
+                buf.push(2);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(authority.as_ref());
+            }
+            Self::AuthorityNominated { record, nominee } => {
+                buf.push(3);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(nominee.as_ref());
+            }
+            Self::RecordResized {
+                record,
+                data_length,
+            } => {
+                buf.push(4);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(&data_length.to_le_bytes());
+            }
+            Self::RecordClosed { record } => {
+                buf.push(5);
+                buf.extend_from_slice(record.as_ref());
+            }
+            Self::RecordMigrated { record, version } => {
+                buf.push(6);
+                buf.extend_from_slice(record.as_ref());
+                buf.push(*version);
+            }
+            Self::DelegationGranted {
+                record,
+                delegate,
+                start,
+                end,
+                expiry_slot,
+            } => {
+                buf.push(7);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(delegate.as_ref());
+                buf.extend_from_slice(&start.to_le_bytes());
+                buf.extend_from_slice(&end.to_le_bytes());
+                buf.extend_from_slice(&expiry_slot.to_le_bytes());
+            }
+            Self::DelegationRevoked { record, delegate } => {
+                buf.push(8);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(delegate.as_ref());
+            }
+            Self::WriterAdded { record, writer } => {
+                buf.push(9);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(writer.as_ref());
+            }
+            Self::WriterRemoved { record, writer } => {
+                buf.push(10);
+                buf.extend_from_slice(record.as_ref());
+                buf.extend_from_slice(writer.as_ref());
+            }
+            Self::NominationCancelled { record } => {
+                buf.push(11);
+                buf.extend_from_slice(record.as_ref());
+            }
+            Self::RecordPaused { record } => {
+                buf.push(12);
+                buf.extend_from_slice(record.as_ref());
+            }
+            Self::RecordUnpaused { record } => {
+                buf.push(13);
+                buf.extend_from_slice(record.as_ref());
+            }
+        }
+        buf
+    }
+
+    /// unpack an event logged by [`Self::emit`]
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&version, rest) = input.split_first().ok_or(ProgramError::InvalidArgument)?;
+        if version != EVENT_SCHEMA_VERSION {
+            msg!("Unsupported event schema version {}", version);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let (&tag, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+        let (record, rest) = unpack_pubkey(rest)?;
+        let (event, rest) = match tag {
+            0 => {
+                let (authority, rest) = unpack_pubkey(rest)?;
+                (Self::RecordInitialized { record, authority }, rest)
+            }
+            1 => {
+                let (writer, rest) = unpack_pubkey(rest)?;
+                let (sequence, rest) = unpack_u64(rest)?;
+                let (offset, rest) = unpack_u64(rest)?;
+                let (len, rest) = unpack_u64(rest)?;
+                let (hash, rest) = rest
+                    .split_first_chunk::<32>()
+                    .ok_or(ProgramError::InvalidArgument)?;
+                let event = Self::RecordWritten {
+                    record,
+                    writer,
+                    sequence,
+                    offset,
+                    len,
+                    hash: *hash,
+                };
+                (event, rest)
+            }
+            2 => {
+                let (authority, rest) = unpack_pubkey(rest)?;
+                (Self::AuthorityChanged { record, authority }, rest)
+            }
+            3 => {
+                let (nominee, rest) = unpack_pubkey(rest)?;
+                (Self::AuthorityNominated { record, nominee }, rest)
+            }
+            4 => {
+                let (data_length, rest) = unpack_u64(rest)?;
+                let event = Self::RecordResized {
+                    record,
+                    data_length,
+                };
+                (event, rest)
+            }
+            5 => (Self::RecordClosed { record }, rest),
+            6 => {
+                let (&version, rest) = rest.split_first().ok_or(ProgramError::InvalidArgument)?;
+                (Self::RecordMigrated { record, version }, rest)
+            }
+            7 => {
+                let (delegate, rest) = unpack_pubkey(rest)?;
+                let (start, rest) = unpack_u64(rest)?;
+                let (end, rest) = unpack_u64(rest)?;
+                let (expiry_slot, rest) = unpack_u64(rest)?;
+                let event = Self::DelegationGranted {
+                    record,
+                    delegate,
+                    start,
+                    end,
+                    expiry_slot,
+                };
+                (event, rest)
+            }
+            8 => {
+                let (delegate, rest) = unpack_pubkey(rest)?;
+                (Self::DelegationRevoked { record, delegate }, rest)
+            }
+            9 => {
+                let (writer, rest) = unpack_pubkey(rest)?;
+                (Self::WriterAdded { record, writer }, rest)
+            }
+            10 => {
+                let (writer, rest) = unpack_pubkey(rest)?;
+                (Self::WriterRemoved { record, writer }, rest)
+            }
+            11 => (Self::NominationCancelled { record }, rest),
+            12 => (Self::RecordPaused { record }, rest),
+            13 => (Self::RecordUnpaused { record }, rest),
+            _ => return Err(ProgramError::InvalidArgument),
+        };
+        if !rest.is_empty() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(event)
+    }
+
+    /// log the event
+    pub fn emit(&self) {
+        emit(&self.pack());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, multisig::event::MultisigEvent};
+
+    #[test]
+    fn pack_unpack_events() {
+        let record = Pubkey::new_from_array([1; 32]);
+        let other = Pubkey::new_from_array([2; 32]);
+        let events = [
+            RecordEvent::RecordInitialized {
+                record,
+                authority: other,
+            },
+            RecordEvent::RecordWritten {
+                record,
+                writer: other,
+                sequence: 3,
+                offset: 8,
+                len: 4,
+                hash: [5; 32],
+            },
+            RecordEvent::AuthorityChanged {
+                record,
+                authority: other,
+            },
+            RecordEvent::AuthorityNominated {
+                record,
+                nominee: other,
+            },
+            RecordEvent::RecordResized {
+                record,
+                data_length: 100,
+            },
+            RecordEvent::RecordClosed { record },
+            RecordEvent::RecordMigrated { record, version: 2 },
+            RecordEvent::DelegationGranted {
+                record,
+                delegate: other,
+                start: 1,
+                end: 9,
+                expiry_slot: 1234,
+            },
+            RecordEvent::DelegationRevoked {
+                record,
+                delegate: other,
+            },
+            RecordEvent::WriterAdded {
+                record,
+                writer: other,
+            },
+            RecordEvent::WriterRemoved {
+                record,
+                writer: other,
+            },
+            RecordEvent::NominationCancelled { record },
+            RecordEvent::RecordPaused { record },
+            RecordEvent::RecordUnpaused { record },
+        ];
+        for event in events {
+            let packed = event.pack();
+            assert_eq!(packed[0], EVENT_SCHEMA_VERSION);
+            assert!(packed[1] < MultisigEvent::FIRST_TAG);
+            assert_eq!(RecordEvent::unpack(&packed).unwrap(), event);
+        }
+    }
+
+    #[test]
+    fn unpack_invalid_events() {
+        let packed = RecordEvent::RecordClosed {
+            record: Pubkey::new_from_array([1; 32]),
+        }
+        .pack();
+
+        let mut trailing = packed.clone();
+        trailing.push(0);
+        assert!(RecordEvent::unpack(&trailing).is_err());
+        assert!(RecordEvent::unpack(&packed[..packed.len() - 1]).is_err());
+
+        let multisig_event = MultisigEvent::ProposalClosed {
+            proposal: Pubkey::new_from_array([1; 32]),
+        }
+        .pack();
+        assert!(RecordEvent::unpack(&multisig_event).is_err());
+    }
+}
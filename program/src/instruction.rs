@@ -1,12 +1,18 @@
 //! Program instructions
 
-use crate::id;
+use crate::{
+    id,
+    state::{AccessList, Delegation},
+};
 
 use {
+    num_derive::FromPrimitive,
+    num_traits::FromPrimitive,
     solana_instruction::{AccountMeta, Instruction},
     solana_msg::msg,
     solana_program_error::ProgramError,
     solana_pubkey::Pubkey,
+    solana_sdk_ids::sysvar,
 };
 
 /// Instructions supported by the program
@@ -25,7 +31,9 @@ pub enum RecordInstruction<'a> {
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable]` Record account, must be previously initialized
-    /// 1. `[signer]` Current record authority
+    /// 1. `[signer]` Current record authority, a delegate or a listed writer
+    /// 2. `[]` Optional, delegation of the signer, see `GrantDelegation`, or
+    ///    access list of the record, see `AddWriter`
     Write {
         /// Offset to start writing record, expressed as `u64`.
         offset: u64,
@@ -45,22 +53,30 @@ pub enum RecordInstruction<'a> {
     /// Close the provided record account, draining lamports to recipient
     /// account
     ///
+    /// The record data is zeroed and the account handed back to the system
+    /// program, so it cannot be revived later in the same transaction. Its
+    /// delegations and access list must be closed first.
+    ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable]` Record account, must be previously initialized
     /// 1. `[signer]` Record authority
-    /// 2. `[]` Receiver of account lamports
+    /// 2. `[writable]` Receiver of account lamports, not the record account
     CloseAccount,
 
-    /// Reallocate additional space in a record account
+    /// Resize a record account to hold the specified data length
     ///
-    /// If the record account already has enough space to hold the specified
-    /// data length, then the instruction does nothing.
+    /// When growing, the payer tops up the lamports needed to stay rent
+    /// exempt. When shrinking, the data past the new length is dropped and
+    /// the lamports above the rent exempt minimum go to the receiver.
     ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable]` The record account to reallocate
     /// 1. `[signer]` The account's owner
+    /// 2. `[writable, signer]` Payer of additional rent
+    /// 3. `[writable]` Receiver of refunded rent
+    /// 4. `[]` System program
     Reallocate {
         /// The length of the data to hold in the record account excluding meta
         /// data
@@ -72,143 +88,902 @@ pub enum RecordInstruction<'a> {
     ///
     /// Propose a multisig write to a record account
     ///
+    /// The proposal account must hold the proposal metadata, the description
+    /// and the packed write (without description). The proposal is registered
+    /// among the active proposals of the multisig.
+    ///
     /// Accounts expected:
-    /// 0. `[signer]` Proposer (must be signer in multisig config)
-    /// 1. `[writable]` Proposal account (PDA, uninitialized)
-    /// 2. `[]` Multisig config account
-    /// 3. `[]` Target record account
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized), a zeroed account
+    ///    owned by the program and large enough for the proposal metadata,
+    ///    the description and the packed write
+    /// 2. `[writable]` Target record account
+    /// 3. `[writable]` Multisig config account
     ProposeMultiWrite {
         /// offset in data
         offset: u64,
         /// data to be written
         data: &'a [u8],
+        /// optional human-readable description (title, link, rationale),
+        /// at most `multisig::proposal::Proposal::MAX_DESCRIPTION_LEN` bytes.
+        /// Stored with the proposal, it is not part of the executed write.
+        description: &'a [u8],
     },
     /// Approve an existing multisig proposal. If threshold is reached, executes write.
     ///
     /// Accounts expected:
-    /// 0. `[signer]` Approver
+    /// 0. `[signer]` Approver, or the child multisig config if the approver
+    ///    slot holds a nested multisig (not a signer in that case)
+    /// 1. `[writable]` Proposal account
+    /// 2. `[writable]` Target record account
+    /// 3. `[writable]` Multisig config account
+    /// 4. `[]` Executed nested approval proposal of the child multisig, only
+    ///    when approving on behalf of a nested multisig
+    ApproveProposal,
+
+    /// Propose that a child multisig approves a proposal of a parent multisig
+    /// it is a member of. Once this proposal reaches the child's threshold,
+    /// `ApproveProposal` can be sent to the parent with the child's slot.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer, a keypair member of the child multisig
+    /// 1. `[writable]` Child proposal account (uninitialized)
+    /// 2. `[]` Parent proposal account to approve
+    /// 3. `[writable]` Child multisig config account
+    /// 4. `[]` Parent multisig config account
+    ProposeNestedApproval,
+
+    /// Approve an existing multisig proposal with signatures collected
+    /// off-chain, recording several approvals in a single transaction. If
+    /// threshold is reached, executes write.
+    ///
+    /// Each approving member signs the proposal's approval digest (see
+    /// `multisig::proposal::Proposal::approval_digest`), and the signatures
+    /// are verified by Ed25519 program instructions placed before this one.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` Proposal account
+    /// 1. `[writable]` Target record account
+    /// 2. `[writable]` Multisig config account
+    /// 3. `[]` Instructions sysvar
+    ApproveWithSignatures,
+
+    /// Approve an existing multisig proposal on behalf of an Ethereum member.
+    /// If threshold is reached, executes write.
+    ///
+    /// The member signs the proposal's Ethereum approval digest (see
+    /// `multisig::proposal::Proposal::eth_approval_digest`) with
+    /// `personal_sign`; the signer is recovered with `secp256k1_recover`.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` Proposal account
+    /// 1. `[writable]` Target record account
+    /// 2. `[writable]` Multisig config account
+    ApproveWithEthSignature {
+        /// Recovery id of the signature, either `0`/`1` or Ethereum's `27`/`28`
+        recovery_id: u8,
+        /// Compact `r || s` secp256k1 signature
+        signature: [u8; 64],
+    },
+
+    /// Pause a multisig in an emergency, e.g. after a signer key leaked.
+    /// While paused, proposals cannot be submitted, approved or executed
+    /// except for unpause proposals, which freezes every record whose
+    /// authority is the multisig. Pausing and unpausing void the approvals
+    /// signed off-chain beforehand, see the config `generation`.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Multisig guardian
+    /// 1. `[writable]` Multisig config account
+    PauseMultisig,
+
+    /// Propose to unpause a paused multisig. The proposal is approved with
+    /// `ApproveProposal`, passing the multisig config as the (writable)
+    /// target account, and needs the regular threshold to execute.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized)
+    /// 2. `[writable]` Multisig config account
+    ProposeUnpauseMultisig,
+
+    /// Close a proposal, executed or not, draining its lamports to the
+    /// receiver and removing it from the active proposals of its multisig.
+    ///
+    /// The proposal data is zeroed and the account handed back to the system
+    /// program, so it cannot be revived later in the same transaction.
+    ///
+    /// Pending proposals of a multisig are listed by the
+    /// `active_proposals` of its config.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Multisig member
     /// 1. `[writable]` Proposal account
+    /// 2. `[writable]` Multisig config account
+    /// 3. `[writable]` Receiver of proposal lamports
+    CloseProposal,
+
+    /// Create a proposal buffer, for multisig writes too large to fit in a
+    /// single `ProposeMultiWrite` transaction. The payload, a packed
+    /// `ProposeMultiWrite` without description, is then uploaded with
+    /// `WriteProposalBuffer` and the proposal opened for approvals with
+    /// `FinalizeProposalBuffer`.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized), large enough for the
+    ///    proposal metadata, the description and the whole payload
+    /// 2. `[]` Target record account
+    /// 3. `[writable]` Multisig config account
+    CreateProposalBuffer {
+        /// optional human-readable description, see `ProposeMultiWrite`
+        description: &'a [u8],
+    },
+
+    /// Write a chunk of the payload of a proposal buffer
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer
+    /// 1. `[writable]` Proposal account, not finalized yet
+    WriteProposalBuffer {
+        /// Offset of the chunk in the payload, expressed as `u64`.
+        offset: u64,
+        /// Chunk of the payload
+        data: &'a [u8],
+    },
+
+    /// Finalize a proposal buffer: hash the uploaded payload and open the
+    /// proposal for approvals
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer
+    /// 1. `[writable]` Proposal account, not finalized yet
+    FinalizeProposalBuffer,
+
+    /// Append to the provided record account, at the end of the data in use
+    ///
+    /// The account is reallocated if the data does not fit, the payer
+    /// topping up the lamports needed to stay rent exempt.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[writable, signer]` Payer of additional rent
+    /// 3. `[]` System program
+    Append {
+        /// Data to append to the record
+        data: &'a [u8],
+    },
+
+    /// Write to the provided record account, only if the bytes about to be
+    /// replaced still hash to `expected_hash`, see `RecordData::hash_contents`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority, a delegate or a listed writer
+    /// 2. `[]` Optional, delegation of the signer, see `GrantDelegation`, or
+    ///    access list of the record, see `AddWriter`
+    WriteIfMatch {
+        /// Offset to start writing record, expressed as `u64`.
+        offset: u64,
+        /// Hash of the record data currently in `offset..offset + data.len()`
+        expected_hash: [u8; 32],
+        /// Data to replace the existing record data
+        data: &'a [u8],
+    },
+
+    /// Propose a multisig write to a record account, executed only if the
+    /// replaced bytes still hash to `expected_hash` when the threshold is
+    /// reached. Approved with `ApproveProposal` like `ProposeMultiWrite`.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized), a zeroed account
+    ///    owned by the program and large enough for the proposal metadata,
+    ///    the description and the packed write
     /// 2. `[writable]` Target record account
-    /// 3. `[]` Multisig config account
+    /// 3. `[writable]` Multisig config account
+    ProposeMultiWriteIfMatch {
+        /// offset in data
+        offset: u64,
+        /// Hash of the record data currently in `offset..offset + data.len()`
+        expected_hash: [u8; 32],
+        /// data to be written
+        data: &'a [u8],
+        /// optional human-readable description, see `ProposeMultiWrite`
+        description: &'a [u8],
+    },
+
+    /// Upgrade a record account with a v1 header to the current header
+    /// layout, shifting its data behind the larger header
+    ///
+    /// The account is reallocated, the payer topping up the lamports needed
+    /// to stay rent exempt.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account with a v1 header
+    /// 1. `[signer]` Current record authority
+    /// 2. `[writable, signer]` Payer of additional rent
+    /// 3. `[]` System program
+    Migrate,
+
+    /// Write several segments of the provided record account at once
+    ///
+    /// All segments are checked against the account bounds before any of
+    /// them is written. Overlapping segments are applied in order.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    WriteBatch {
+        /// Offset and data of every segment to write
+        segments: Vec<(u64, &'a [u8])>,
+    },
+
+    /// Create a record account funded by the payer and initialize it
+    ///
+    /// Without seeds the record account is a fresh keypair signing the
+    /// transaction, otherwise it is the program address derived from the
+    /// seeds, the bump seed included, behind the `record` prefix, see
+    /// `RecordData::find_address`. Seeds cannot start with the prefix of
+    /// delegation or access list addresses.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable, signer]` Record account, signer only without seeds
+    /// 1. `[]` Record authority
+    /// 2. `[writable, signer]` Payer of the account rent
+    /// 3. `[]` System program
+    CreateAndInitialize {
+        /// The length of the data to hold in the record account excluding meta
+        /// data
+        data_length: u64,
+        /// Seeds of the record program address, empty for a keypair account
+        seeds: Vec<&'a [u8]>,
+    },
+
+    /// Renounce the authority of the provided record account, freezing it
+    ///
+    /// Every instruction changing the record fails afterwards, closing
+    /// included.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    Freeze,
+
+    /// Nominate a new authority of the provided record account, which takes
+    /// over only once it accepts with `AcceptAuthority`. A later nomination
+    /// replaces the pending one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[]` Nominated record authority
+    NominateAuthority,
+
+    /// Accept the nomination as authority of the provided record account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Nominated record authority
+    AcceptAuthority,
+
+    /// Cancel the pending authority nomination of the provided record account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    CancelAuthorityNomination,
+
+    /// Allow a delegate to write a byte range of the provided record account,
+    /// replacing its previous delegation if any
+    ///
+    /// The delegation is stored at the program address derived by
+    /// `Delegation::find_address`, the payer funding its rent. The record
+    /// counts it until it is revoked, and v1 records cannot hold any.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[]` Delegate
+    /// 3. `[writable]` Delegation account
+    /// 4. `[writable, signer]` Payer of the delegation rent
+    /// 5. `[]` System program
+    GrantDelegation {
+        /// Start of the writable range, relative to the start of record data
+        start: u64,
+        /// End of the writable range, exclusive
+        end: u64,
+        /// Last slot the delegation is valid in, `Delegation::NO_EXPIRY`
+        /// for delegations valid until revoked
+        expiry_slot: u64,
+    },
+
+    /// Revoke a delegation of the provided record account, closing the
+    /// delegation account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[writable]` Delegation account
+    /// 3. `[writable]` Receiver of the delegation lamports
+    RevokeDelegation,
+
+    /// Allow a writer to write anywhere in the provided record account,
+    /// alongside its authority and the other listed writers
+    ///
+    /// The writers are listed at the program address derived by
+    /// `AccessList::find_address`, created on the first addition, the
+    /// payer funding its rent. The record counts it until its last writer is
+    /// removed, and v1 records cannot hold one.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[]` Writer to add
+    /// 3. `[writable]` Access list account
+    /// 4. `[writable, signer]` Payer of the access list rent
+    /// 5. `[]` System program
+    AddWriter,
+
+    /// Remove a writer from the access list of the provided record account,
+    /// closing the access list account once its last writer is removed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Record account, must be previously initialized
+    /// 1. `[signer]` Current record authority
+    /// 2. `[]` Writer to remove
+    /// 3. `[writable]` Access list account
+    /// 4. `[writable]` Receiver of the access list lamports
+    RemoveWriter,
+
+    /// Upgrade a multisig config with a v1 layout to the current layout,
+    /// approved by a threshold of its signers, who all become keypair members
+    ///
+    /// The account is reallocated, the payer topping up the lamports needed
+    /// to stay rent exempt.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` Multisig config account with a v1 layout
+    /// 1. `[writable, signer]` Payer of additional rent
+    /// 2. `[]` System program
+    /// 3. ..`3+M` `[signer]` M signers of the multisig
+    MigrateMultisig,
+
+    /// Upgrade a proposal with a v1 layout to the current layout, shifting
+    /// its payload behind the larger metadata
+    ///
+    /// The multisig must be migrated first. The proposal gets the next index
+    /// of the multisig and, if not executed yet, an active proposal slot.
+    /// The account is reallocated, the payer topping up the lamports needed
+    /// to stay rent exempt.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Keypair member of the multisig, recorded as proposer
+    /// 1. `[writable]` Proposal account with a v1 layout
+    /// 2. `[writable]` Multisig config account
+    /// 3. `[writable, signer]` Payer of additional rent
+    /// 4. `[]` System program
+    MigrateProposal,
+
+    /// Pause a record controlled by a multisig in an emergency. While
+    /// paused, the record cannot be written, reallocated, closed, migrated
+    /// or handed to another authority, nor can its delegations and writers
+    /// change, including through multisig proposals, until an unpause
+    /// proposal passes.
+    ///
+    /// Records with a v1 header cannot be paused.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Guardian of the multisig
+    /// 1. `[writable]` Record account, must be previously initialized
+    /// 2. `[]` Multisig config account, the record authority
+    PauseRecord,
+
+    /// Propose to unpause a paused record. The proposal is approved with
+    /// `ApproveProposal` like a write and needs the regular threshold of the
+    /// multisig to execute.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized)
+    /// 2. `[writable]` Paused record account
+    /// 3. `[writable]` Multisig config account, the record authority
+    ProposeUnpauseRecord {
+        /// optional human-readable description, see `ProposeMultiWrite`
+        description: &'a [u8],
+    },
+
+    /// Propose to set the guardian of a multisig, which may pause the
+    /// multisig and the records it controls. The proposal is approved with
+    /// `ApproveProposal`, passing the multisig config as the (writable)
+    /// target account, and needs the regular threshold to execute.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` Proposer, a keypair member of the multisig
+    /// 1. `[writable]` Proposal account (uninitialized)
+    /// 2. `[writable]` Multisig config account
+    ProposeSetGuardian {
+        /// New guardian, the default key to leave the multisig without one
+        guardian: Pubkey,
+        /// optional human-readable description, see `ProposeMultiWrite`
+        description: &'a [u8],
+    },
+}
+
+/// Discriminant of a [`RecordInstruction`], the first byte of its data.
+///
+/// The values are part of the wire format and mirrored in
+/// `program/instruction-spec.json`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq)]
+pub enum RecordInstructionTag {
+    /// [`RecordInstruction::Initialize`]
+    Initialize = 0,
+    /// [`RecordInstruction::Write`]
+    Write,
+    /// [`RecordInstruction::SetAuthority`]
+    SetAuthority,
+    /// [`RecordInstruction::CloseAccount`]
+    CloseAccount,
+    /// [`RecordInstruction::Reallocate`]
+    Reallocate,
+    /// [`RecordInstruction::ProposeMultiWrite`]
+    ProposeMultiWrite,
+    /// [`RecordInstruction::ApproveProposal`]
     ApproveProposal,
+    /// [`RecordInstruction::ProposeNestedApproval`]
+    ProposeNestedApproval,
+    /// [`RecordInstruction::ApproveWithSignatures`]
+    ApproveWithSignatures,
+    /// [`RecordInstruction::ApproveWithEthSignature`]
+    ApproveWithEthSignature,
+    /// [`RecordInstruction::PauseMultisig`]
+    PauseMultisig,
+    /// [`RecordInstruction::ProposeUnpauseMultisig`]
+    ProposeUnpauseMultisig,
+    /// [`RecordInstruction::CloseProposal`]
+    CloseProposal,
+    /// [`RecordInstruction::CreateProposalBuffer`]
+    CreateProposalBuffer,
+    /// [`RecordInstruction::WriteProposalBuffer`]
+    WriteProposalBuffer,
+    /// [`RecordInstruction::FinalizeProposalBuffer`]
+    FinalizeProposalBuffer,
+    /// [`RecordInstruction::Append`]
+    Append,
+    /// [`RecordInstruction::WriteIfMatch`]
+    WriteIfMatch,
+    /// [`RecordInstruction::ProposeMultiWriteIfMatch`]
+    ProposeMultiWriteIfMatch,
+    /// [`RecordInstruction::Migrate`]
+    Migrate,
+    /// [`RecordInstruction::WriteBatch`]
+    WriteBatch,
+    /// [`RecordInstruction::CreateAndInitialize`]
+    CreateAndInitialize,
+    /// [`RecordInstruction::Freeze`]
+    Freeze,
+    /// [`RecordInstruction::NominateAuthority`]
+    NominateAuthority,
+    /// [`RecordInstruction::AcceptAuthority`]
+    AcceptAuthority,
+    /// [`RecordInstruction::CancelAuthorityNomination`]
+    CancelAuthorityNomination,
+    /// [`RecordInstruction::GrantDelegation`]
+    GrantDelegation,
+    /// [`RecordInstruction::RevokeDelegation`]
+    RevokeDelegation,
+    /// [`RecordInstruction::AddWriter`]
+    AddWriter,
+    /// [`RecordInstruction::RemoveWriter`]
+    RemoveWriter,
+    /// [`RecordInstruction::MigrateMultisig`]
+    MigrateMultisig,
+    /// [`RecordInstruction::MigrateProposal`]
+    MigrateProposal,
+    /// [`RecordInstruction::PauseRecord`]
+    PauseRecord,
+    /// [`RecordInstruction::ProposeUnpauseRecord`]
+    ProposeUnpauseRecord,
+    /// [`RecordInstruction::ProposeSetGuardian`]
+    ProposeSetGuardian,
+}
+
+impl TryFrom<u8> for RecordInstructionTag {
+    type Error = ProgramError;
+
+    fn try_from(tag: u8) -> Result<Self, Self::Error> {
+        Self::from_u8(tag).ok_or_else(|| {
+            msg!("Unknown instruction tag {}", tag);
+            ProgramError::InvalidInstructionData
+        })
+    }
+}
+
+impl From<RecordInstructionTag> for u8 {
+    fn from(tag: RecordInstructionTag) -> Self {
+        tag as u8
+    }
 }
 
 impl<'a> RecordInstruction<'a> {
     /// Unpacks a byte buffer into a [`RecordInstruction`].
     pub fn unpack(input: &'a [u8]) -> Result<Self, ProgramError> {
-        const U32_BYTES: usize = 4;
-        const U64_BYTES: usize = 8;
-
-        let (&tag, instruction_data) = input.split_first().ok_or_else(|| {
-            msg!("Cannot split correctly input! input: {:?}", &input);
+        let (&tag, mut rest) = input.split_first().ok_or_else(|| {
+            msg!("Instruction data is empty");
             ProgramError::InvalidInstructionData
         })?;
-        // TODO instead of instruction tags we should use into() from enums
-        Ok(match tag {
-            0 => Self::Initialize,
-            1 => {
-                let offset = instruction_data
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or_else(|| {
-                        msg!("instr 1 - Cannot map correctly!");
-                        ProgramError::InvalidInstructionData
-                    })?;
-                let (length, data) = instruction_data[U64_BYTES..].split_at(U32_BYTES);
-                let length = u32::from_le_bytes(length.try_into().map_err(|_| {
-                    msg!("Instr 1 - Cannot split by length or sth");
-                    ProgramError::InvalidInstructionData
-                })?) as usize;
-
-                Self::Write {
-                    offset,
-                    data: &data[..length],
+        let instruction = match RecordInstructionTag::try_from(tag)? {
+            RecordInstructionTag::Initialize => Self::Initialize,
+            RecordInstructionTag::Write => Self::Write {
+                offset: unpack_u64(&mut rest)?,
+                data: unpack_data(&mut rest)?,
+            },
+            RecordInstructionTag::SetAuthority => Self::SetAuthority,
+            RecordInstructionTag::CloseAccount => Self::CloseAccount,
+            RecordInstructionTag::Reallocate => Self::Reallocate {
+                data_length: unpack_u64(&mut rest)?,
+            },
+            RecordInstructionTag::ProposeMultiWrite => Self::ProposeMultiWrite {
+                offset: unpack_u64(&mut rest)?,
+                data: unpack_data(&mut rest)?,
+                description: unpack_description(&mut rest)?,
+            },
+            RecordInstructionTag::ApproveProposal => Self::ApproveProposal,
+            RecordInstructionTag::ProposeNestedApproval => Self::ProposeNestedApproval,
+            RecordInstructionTag::ApproveWithSignatures => Self::ApproveWithSignatures,
+            RecordInstructionTag::ApproveWithEthSignature => Self::ApproveWithEthSignature {
+                recovery_id: unpack_u8(&mut rest)?,
+                signature: *unpack_array(&mut rest)?,
+            },
+            RecordInstructionTag::PauseMultisig => Self::PauseMultisig,
+            RecordInstructionTag::ProposeUnpauseMultisig => Self::ProposeUnpauseMultisig,
+            RecordInstructionTag::CloseProposal => Self::CloseProposal,
+            RecordInstructionTag::CreateProposalBuffer => Self::CreateProposalBuffer {
+                description: unpack_description(&mut rest)?,
+            },
+            RecordInstructionTag::WriteProposalBuffer => Self::WriteProposalBuffer {
+                offset: unpack_u64(&mut rest)?,
+                data: unpack_data(&mut rest)?,
+            },
+            RecordInstructionTag::FinalizeProposalBuffer => Self::FinalizeProposalBuffer,
+            RecordInstructionTag::Append => Self::Append {
+                data: unpack_data(&mut rest)?,
+            },
+            RecordInstructionTag::WriteIfMatch => Self::WriteIfMatch {
+                offset: unpack_u64(&mut rest)?,
+                expected_hash: *unpack_array(&mut rest)?,
+                data: unpack_data(&mut rest)?,
+            },
+            RecordInstructionTag::ProposeMultiWriteIfMatch => Self::ProposeMultiWriteIfMatch {
+                offset: unpack_u64(&mut rest)?,
+                expected_hash: *unpack_array(&mut rest)?,
+                data: unpack_data(&mut rest)?,
+                description: unpack_description(&mut rest)?,
+            },
+            RecordInstructionTag::Migrate => Self::Migrate,
+            RecordInstructionTag::WriteBatch => {
+                let count = unpack_u16(&mut rest)?;
+                // not preallocated, the count is not trusted before the segments are read
+                let mut segments = Vec::new();
+                for _ in 0..count {
+                    segments.push((unpack_u64(&mut rest)?, unpack_data(&mut rest)?));
                 }
+                Self::WriteBatch { segments }
             }
-            2 => Self::SetAuthority,
-            3 => Self::CloseAccount,
-            4 => {
-                let data_length = instruction_data
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or_else(|| {
-                        msg!("instr 4 - Cannot slice instr data correctly!");
-                        ProgramError::InvalidInstructionData
-                    })?;
-
-                Self::Reallocate { data_length }
-            }
-            5 => {
-                let offset = instruction_data
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or_else(|| {
-                        msg!("instr 5 - Cannot slice instr data correctly!");
-                        ProgramError::InvalidInstructionData
-                    })?;
-
-                let (data_len, data) = instruction_data[U64_BYTES..].split_at(U32_BYTES);
-
-                let data_len = u32::from_le_bytes(data_len.try_into().map_err(|e| {
-                    msg!("Instr 5 - error: {:?}", e);
-                    ProgramError::InvalidInstructionData
-                })?) as usize;
-
-                if data_len > data.len() {
-                    msg!("Instr 5 - Length required greater than data length available: data len: {}, data.len(): {}", data_len, data.len());
-                    return Err(ProgramError::InvalidInstructionData);
-                }
-
-                Self::ProposeMultiWrite {
-                    offset,
-                    data: &data[..data_len],
+            RecordInstructionTag::CreateAndInitialize => {
+                let data_length = unpack_u64(&mut rest)?;
+                let count = unpack_u8(&mut rest)?;
+                let mut seeds = Vec::new();
+                for _ in 0..count {
+                    let seed_len = unpack_u8(&mut rest)?;
+                    seeds.push(unpack_bytes(&mut rest, seed_len as usize)?);
                 }
+                Self::CreateAndInitialize { data_length, seeds }
             }
-            6 => Self::ApproveProposal,
+            RecordInstructionTag::Freeze => Self::Freeze,
+            RecordInstructionTag::NominateAuthority => Self::NominateAuthority,
+            RecordInstructionTag::AcceptAuthority => Self::AcceptAuthority,
+            RecordInstructionTag::CancelAuthorityNomination => Self::CancelAuthorityNomination,
+            RecordInstructionTag::GrantDelegation => Self::GrantDelegation {
+                start: unpack_u64(&mut rest)?,
+                end: unpack_u64(&mut rest)?,
+                expiry_slot: unpack_u64(&mut rest)?,
+            },
+            RecordInstructionTag::RevokeDelegation => Self::RevokeDelegation,
+            RecordInstructionTag::AddWriter => Self::AddWriter,
+            RecordInstructionTag::RemoveWriter => Self::RemoveWriter,
+            RecordInstructionTag::MigrateMultisig => Self::MigrateMultisig,
+            RecordInstructionTag::MigrateProposal => Self::MigrateProposal,
+            RecordInstructionTag::PauseRecord => Self::PauseRecord,
+            RecordInstructionTag::ProposeUnpauseRecord => Self::ProposeUnpauseRecord {
+                description: unpack_description(&mut rest)?,
+            },
+            RecordInstructionTag::ProposeSetGuardian => Self::ProposeSetGuardian {
+                guardian: Pubkey::new_from_array(*unpack_array(&mut rest)?),
+                description: unpack_description(&mut rest)?,
+            },
+        };
+        if !rest.is_empty() {
+            msg!("Instruction {} has {} trailing bytes", tag, rest.len());
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(instruction)
+    }
 
-            _ => {
-                msg!("Unknown Instruction data");
-                return Err(ProgramError::InvalidInstructionData);
-            }
-        })
+    /// Discriminant of the instruction
+    pub fn tag(&self) -> RecordInstructionTag {
+        match self {
+            Self::Initialize => RecordInstructionTag::Initialize,
+            Self::Write { .. } => RecordInstructionTag::Write,
+            Self::SetAuthority => RecordInstructionTag::SetAuthority,
+            Self::CloseAccount => RecordInstructionTag::CloseAccount,
+            Self::Reallocate { .. } => RecordInstructionTag::Reallocate,
+            Self::ProposeMultiWrite { .. } => RecordInstructionTag::ProposeMultiWrite,
+            Self::ApproveProposal => RecordInstructionTag::ApproveProposal,
+            Self::ProposeNestedApproval => RecordInstructionTag::ProposeNestedApproval,
+            Self::ApproveWithSignatures => RecordInstructionTag::ApproveWithSignatures,
+            Self::ApproveWithEthSignature { .. } => RecordInstructionTag::ApproveWithEthSignature,
+            Self::PauseMultisig => RecordInstructionTag::PauseMultisig,
+            Self::ProposeUnpauseMultisig => RecordInstructionTag::ProposeUnpauseMultisig,
+            Self::CloseProposal => RecordInstructionTag::CloseProposal,
+            Self::CreateProposalBuffer { .. } => RecordInstructionTag::CreateProposalBuffer,
+            Self::WriteProposalBuffer { .. } => RecordInstructionTag::WriteProposalBuffer,
+            Self::FinalizeProposalBuffer => RecordInstructionTag::FinalizeProposalBuffer,
+            Self::Append { .. } => RecordInstructionTag::Append,
+            Self::WriteIfMatch { .. } => RecordInstructionTag::WriteIfMatch,
+            Self::ProposeMultiWriteIfMatch { .. } => RecordInstructionTag::ProposeMultiWriteIfMatch,
+            Self::Migrate => RecordInstructionTag::Migrate,
+            Self::WriteBatch { .. } => RecordInstructionTag::WriteBatch,
+            Self::CreateAndInitialize { .. } => RecordInstructionTag::CreateAndInitialize,
+            Self::Freeze => RecordInstructionTag::Freeze,
+            Self::NominateAuthority => RecordInstructionTag::NominateAuthority,
+            Self::AcceptAuthority => RecordInstructionTag::AcceptAuthority,
+            Self::CancelAuthorityNomination => RecordInstructionTag::CancelAuthorityNomination,
+            Self::GrantDelegation { .. } => RecordInstructionTag::GrantDelegation,
+            Self::RevokeDelegation => RecordInstructionTag::RevokeDelegation,
+            Self::AddWriter => RecordInstructionTag::AddWriter,
+            Self::RemoveWriter => RecordInstructionTag::RemoveWriter,
+            Self::MigrateMultisig => RecordInstructionTag::MigrateMultisig,
+            Self::MigrateProposal => RecordInstructionTag::MigrateProposal,
+            Self::PauseRecord => RecordInstructionTag::PauseRecord,
+            Self::ProposeUnpauseRecord { .. } => RecordInstructionTag::ProposeUnpauseRecord,
+            Self::ProposeSetGuardian { .. } => RecordInstructionTag::ProposeSetGuardian,
+        }
     }
 
     /// Packs a [`RecordInstruction`] into a byte buffer.
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
+        buf.push(self.tag().into());
         match self {
-            Self::Initialize => buf.push(0),
             Self::Write { offset, data } => {
-                buf.push(1);
                 buf.extend_from_slice(&offset.to_le_bytes());
                 buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
                 buf.extend_from_slice(data);
             }
-            Self::SetAuthority => buf.push(2),
-            Self::CloseAccount => buf.push(3),
             Self::Reallocate { data_length } => {
-                buf.push(4);
                 buf.extend_from_slice(&data_length.to_le_bytes());
             }
-            Self::ProposeMultiWrite { offset, data } => {
-                buf.push(5);
+            Self::ProposeMultiWrite {
+                offset,
+                data,
+                description,
+            } => {
                 buf.extend_from_slice(&offset.to_le_bytes());
                 buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
                 buf.extend_from_slice(data);
+                pack_description(&mut buf, description);
             }
-            Self::ApproveProposal => buf.push(6),
+            Self::ApproveWithEthSignature {
+                recovery_id,
+                signature,
+            } => {
+                buf.push(*recovery_id);
+                buf.extend_from_slice(signature);
+            }
+            Self::CreateProposalBuffer { description }
+            | Self::ProposeUnpauseRecord { description } => {
+                pack_description(&mut buf, description);
+            }
+            Self::WriteProposalBuffer { offset, data } => {
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::Append { data } => {
+                buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::WriteIfMatch {
+                offset,
+                expected_hash,
+                data,
+            } => {
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.extend_from_slice(expected_hash);
+                buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::ProposeMultiWriteIfMatch {
+                offset,
+                expected_hash,
+                data,
+                description,
+            } => {
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.extend_from_slice(expected_hash);
+                buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                buf.extend_from_slice(data);
+                pack_description(&mut buf, description);
+            }
+            Self::WriteBatch { segments } => {
+                buf.extend_from_slice(&(segments.len() as u16).to_le_bytes());
+                for (offset, data) in segments {
+                    buf.extend_from_slice(&offset.to_le_bytes());
+                    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                    buf.extend_from_slice(data);
+                }
+            }
+            Self::CreateAndInitialize { data_length, seeds } => {
+                buf.extend_from_slice(&data_length.to_le_bytes());
+                buf.push(seeds.len() as u8);
+                for seed in seeds {
+                    buf.push(seed.len() as u8);
+                    buf.extend_from_slice(seed);
+                }
+            }
+            Self::GrantDelegation {
+                start,
+                end,
+                expiry_slot,
+            } => {
+                buf.extend_from_slice(&start.to_le_bytes());
+                buf.extend_from_slice(&end.to_le_bytes());
+                buf.extend_from_slice(&expiry_slot.to_le_bytes());
+            }
+            Self::ProposeSetGuardian {
+                guardian,
+                description,
+            } => {
+                buf.extend_from_slice(guardian.as_ref());
+                pack_description(&mut buf, description);
+            }
+            Self::Initialize
+            | Self::SetAuthority
+            | Self::CloseAccount
+            | Self::ApproveProposal
+            | Self::ProposeNestedApproval
+            | Self::ApproveWithSignatures
+            | Self::PauseMultisig
+            | Self::ProposeUnpauseMultisig
+            | Self::CloseProposal
+            | Self::FinalizeProposalBuffer
+            | Self::Migrate
+            | Self::Freeze
+            | Self::NominateAuthority
+            | Self::AcceptAuthority
+            | Self::CancelAuthorityNomination
+            | Self::RevokeDelegation
+            | Self::AddWriter
+            | Self::RemoveWriter
+            | Self::MigrateMultisig
+            | Self::MigrateProposal
+            | Self::PauseRecord => {}
         };
         buf
     }
 }
 
+/// Takes `len` bytes off the front of `input`
+fn unpack_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProgramError> {
+    if len > input.len() {
+        msg!(
+            "Instruction data too short: {} bytes needed, {} available",
+            len,
+            input.len()
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
+}
+
+/// Takes a fixed-size array off the front of `input`
+fn unpack_array<'a, const N: usize>(input: &mut &'a [u8]) -> Result<&'a [u8; N], ProgramError> {
+    let (bytes, rest) = input.split_first_chunk::<N>().ok_or_else(|| {
+        msg!(
+            "Instruction data too short: {} bytes needed, {} available",
+            N,
+            input.len()
+        );
+        ProgramError::InvalidInstructionData
+    })?;
+    *input = rest;
+    Ok(bytes)
+}
+
+fn unpack_u8(input: &mut &[u8]) -> Result<u8, ProgramError> {
+    unpack_array::<1>(input).map(|bytes| bytes[0])
+}
+
+fn unpack_u16(input: &mut &[u8]) -> Result<u16, ProgramError> {
+    unpack_array(input).map(|bytes| u16::from_le_bytes(*bytes))
+}
+
+fn unpack_u32(input: &mut &[u8]) -> Result<u32, ProgramError> {
+    unpack_array(input).map(|bytes| u32::from_le_bytes(*bytes))
+}
+
+fn unpack_u64(input: &mut &[u8]) -> Result<u64, ProgramError> {
+    unpack_array(input).map(|bytes| u64::from_le_bytes(*bytes))
+}
+
+/// Takes data prefixed with its `u32` length off the front of `input`
+fn unpack_data<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], ProgramError> {
+    let len = unpack_u32(input)?;
+    let len = usize::try_from(len).map_err(|_| ProgramError::InvalidInstructionData)?;
+    unpack_bytes(input, len)
+}
+
+/// Unpacks the optional trailing description of a proposal: omitted or zero
+/// padding when empty, otherwise its `u16` length followed by its bytes.
+/// Proposal payloads are read back from zero-padded accounts, so padding may
+/// also follow the description.
+fn unpack_description<'a>(input: &mut &'a [u8]) -> Result<&'a [u8], ProgramError> {
+    let is_padding = |input: &[u8]| input.iter().all(|byte| *byte == 0);
+    let description = if is_padding(input) {
+        &[]
+    } else {
+        let description_len = unpack_u16(input)?;
+        unpack_bytes(input, description_len as usize)?
+    };
+    if is_padding(input) {
+        *input = &[];
+    }
+    Ok(description)
+}
+
+/// Packs the optional trailing description of a proposal
+fn pack_description(buf: &mut Vec<u8>, description: &[u8]) {
+    if !description.is_empty() {
+        buf.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        buf.extend_from_slice(description);
+    }
+}
+
 /// Create a `RecordInstruction::Initialize` instruction
 pub fn initialize(record_account: &Pubkey, authority: &Pubkey) -> Instruction {
+    initialize_with_program_id(&id(), record_account, authority)
+}
+
+/// [`initialize`] for the program deployed at `program_id`
+pub fn initialize_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id: *program_id,
         accounts: vec![
             AccountMeta::new(*record_account, false),
             AccountMeta::new_readonly(*authority, false),
@@ -217,57 +992,1228 @@ pub fn initialize(record_account: &Pubkey, authority: &Pubkey) -> Instruction {
     }
 }
 
-/// Create a `RecordInstruction::Write` instruction
-pub fn write(record_account: &Pubkey, signer: &Pubkey, offset: u64, data: &[u8]) -> Instruction {
+/// Create a `RecordInstruction::CreateAndInitialize` instruction for a
+/// keypair record account
+pub fn create_and_initialize(
+    record_account: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    data_length: u64,
+) -> Instruction {
+    create_and_initialize_with_program_id(&id(), record_account, authority, payer, data_length)
+}
+
+/// [`create_and_initialize`] for the program deployed at `program_id`
+pub fn create_and_initialize_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    data_length: u64,
+) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id: *program_id,
         accounts: vec![
-            AccountMeta::new(*record_account, false),
-            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*record_account, true),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
         ],
-        data: RecordInstruction::Write { offset, data }.pack(),
+        data: RecordInstruction::CreateAndInitialize {
+            data_length,
+            seeds: vec![],
+        }
+        .pack(),
     }
 }
 
-/// Create a `RecordInstruction::SetAuthority` instruction
-pub fn set_authority(
+/// Create a `RecordInstruction::CreateAndInitialize` instruction for a
+/// record account at the program address derived from `seeds` by
+/// `RecordData::find_address`, which must include the bump seed
+pub fn create_and_initialize_with_seeds(
     record_account: &Pubkey,
-    signer: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    data_length: u64,
+    seeds: &[&[u8]],
+) -> Instruction {
+    create_and_initialize_with_seeds_with_program_id(
+        &id(),
+        record_account,
+        authority,
+        payer,
+        data_length,
+        seeds,
+    )
+}
+
+/// [`create_and_initialize_with_seeds`] for the program deployed at `program_id`
+pub fn create_and_initialize_with_seeds_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    data_length: u64,
+    seeds: &[&[u8]],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::CreateAndInitialize {
+            data_length,
+            seeds: seeds.to_vec(),
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Write` instruction
+pub fn write(record_account: &Pubkey, signer: &Pubkey, offset: u64, data: &[u8]) -> Instruction {
+    write_with_program_id(&id(), record_account, signer, offset, data)
+}
+
+/// [`write`] for the program deployed at `program_id`
+pub fn write_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+        ],
+        data: RecordInstruction::Write { offset, data }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Write` instruction signed by a delegate
+pub fn write_as_delegate(
+    record_account: &Pubkey,
+    delegate: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    write_as_delegate_with_program_id(&id(), record_account, delegate, offset, data)
+}
+
+/// [`write_as_delegate`] for the program deployed at `program_id`
+pub fn write_as_delegate_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    delegate: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    let (delegation, _) = Delegation::find_address(record_account, delegate, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*delegate, true),
+            AccountMeta::new_readonly(delegation, false),
+        ],
+        data: RecordInstruction::Write { offset, data }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::GrantDelegation` instruction
+pub fn grant_delegation(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    delegate: &Pubkey,
+    payer: &Pubkey,
+    start: u64,
+    end: u64,
+    expiry_slot: u64,
+) -> Instruction {
+    grant_delegation_with_program_id(
+        &id(),
+        record_account,
+        signer,
+        delegate,
+        payer,
+        start,
+        end,
+        expiry_slot,
+    )
+}
+
+/// [`grant_delegation`] for the program deployed at `program_id`
+#[allow(clippy::too_many_arguments)]
+pub fn grant_delegation_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    delegate: &Pubkey,
+    payer: &Pubkey,
+    start: u64,
+    end: u64,
+    expiry_slot: u64,
+) -> Instruction {
+    let (delegation, _) = Delegation::find_address(record_account, delegate, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*delegate, false),
+            AccountMeta::new(delegation, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::GrantDelegation {
+            start,
+            end,
+            expiry_slot,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::RevokeDelegation` instruction
+pub fn revoke_delegation(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    delegate: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    revoke_delegation_with_program_id(&id(), record_account, signer, delegate, receiver)
+}
+
+/// [`revoke_delegation`] for the program deployed at `program_id`
+pub fn revoke_delegation_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    delegate: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    let (delegation, _) = Delegation::find_address(record_account, delegate, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(delegation, false),
+            AccountMeta::new(*receiver, false),
+        ],
+        data: RecordInstruction::RevokeDelegation.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Write` instruction signed by a listed writer
+pub fn write_as_listed_writer(
+    record_account: &Pubkey,
+    writer: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    write_as_listed_writer_with_program_id(&id(), record_account, writer, offset, data)
+}
+
+/// [`write_as_listed_writer`] for the program deployed at `program_id`
+pub fn write_as_listed_writer_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    writer: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    let (access_list, _) = AccessList::find_address(record_account, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*writer, true),
+            AccountMeta::new_readonly(access_list, false),
+        ],
+        data: RecordInstruction::Write { offset, data }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::AddWriter` instruction
+pub fn add_writer(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    writer: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    add_writer_with_program_id(&id(), record_account, signer, writer, payer)
+}
+
+/// [`add_writer`] for the program deployed at `program_id`
+pub fn add_writer_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    writer: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (access_list, _) = AccessList::find_address(record_account, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*writer, false),
+            AccountMeta::new(access_list, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::AddWriter.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::RemoveWriter` instruction
+pub fn remove_writer(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    writer: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    remove_writer_with_program_id(&id(), record_account, signer, writer, receiver)
+}
+
+/// [`remove_writer`] for the program deployed at `program_id`
+pub fn remove_writer_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    writer: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    let (access_list, _) = AccessList::find_address(record_account, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*writer, false),
+            AccountMeta::new(access_list, false),
+            AccountMeta::new(*receiver, false),
+        ],
+        data: RecordInstruction::RemoveWriter.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::WriteBatch` instruction
+pub fn write_batch(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    segments: &[(u64, &[u8])],
+) -> Instruction {
+    write_batch_with_program_id(&id(), record_account, signer, segments)
+}
+
+/// [`write_batch`] for the program deployed at `program_id`
+pub fn write_batch_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    segments: &[(u64, &[u8])],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+        ],
+        data: RecordInstruction::WriteBatch {
+            segments: segments.to_vec(),
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::WriteIfMatch` instruction
+pub fn write_if_match(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    offset: u64,
+    expected_hash: [u8; 32],
+    data: &[u8],
+) -> Instruction {
+    write_if_match_with_program_id(&id(), record_account, signer, offset, expected_hash, data)
+}
+
+/// [`write_if_match`] for the program deployed at `program_id`
+pub fn write_if_match_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    offset: u64,
+    expected_hash: [u8; 32],
+    data: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+        ],
+        data: RecordInstruction::WriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::SetAuthority` instruction
+pub fn set_authority(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    set_authority_with_program_id(&id(), record_account, signer, new_authority)
+}
+
+/// [`set_authority`] for the program deployed at `program_id`
+pub fn set_authority_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*new_authority, false),
+        ],
+        data: RecordInstruction::SetAuthority.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::CloseAccount` instruction
+pub fn close_account(record_account: &Pubkey, signer: &Pubkey, receiver: &Pubkey) -> Instruction {
+    close_account_with_program_id(&id(), record_account, signer, receiver)
+}
+
+/// [`close_account`] for the program deployed at `program_id`
+pub fn close_account_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*receiver, false),
+        ],
+        data: RecordInstruction::CloseAccount.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::NominateAuthority` instruction
+pub fn nominate_authority(
+    record_account: &Pubkey,
+    signer: &Pubkey,
     new_authority: &Pubkey,
+) -> Instruction {
+    nominate_authority_with_program_id(&id(), record_account, signer, new_authority)
+}
+
+/// [`nominate_authority`] for the program deployed at `program_id`
+pub fn nominate_authority_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*new_authority, false),
+        ],
+        data: RecordInstruction::NominateAuthority.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::AcceptAuthority` instruction
+pub fn accept_authority(record_account: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    accept_authority_with_program_id(&id(), record_account, new_authority)
+}
+
+/// [`accept_authority`] for the program deployed at `program_id`
+pub fn accept_authority_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*new_authority, true),
+        ],
+        data: RecordInstruction::AcceptAuthority.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::CancelAuthorityNomination` instruction
+pub fn cancel_authority_nomination(record_account: &Pubkey, signer: &Pubkey) -> Instruction {
+    cancel_authority_nomination_with_program_id(&id(), record_account, signer)
+}
+
+/// [`cancel_authority_nomination`] for the program deployed at `program_id`
+pub fn cancel_authority_nomination_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+        ],
+        data: RecordInstruction::CancelAuthorityNomination.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Freeze` instruction
+pub fn freeze(record_account: &Pubkey, signer: &Pubkey) -> Instruction {
+    freeze_with_program_id(&id(), record_account, signer)
+}
+
+/// [`freeze`] for the program deployed at `program_id`
+pub fn freeze_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+        ],
+        data: RecordInstruction::Freeze.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Reallocate` instruction
+pub fn reallocate(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    receiver: &Pubkey,
+    data_length: u64,
+) -> Instruction {
+    reallocate_with_program_id(&id(), record_account, signer, payer, receiver, data_length)
+}
+
+/// [`reallocate`] for the program deployed at `program_id`
+pub fn reallocate_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    receiver: &Pubkey,
+    data_length: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*receiver, false),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::Reallocate { data_length }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Append` instruction
+pub fn append(
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    data: &[u8],
+) -> Instruction {
+    append_with_program_id(&id(), record_account, signer, payer, data)
+}
+
+/// [`append`] for the program deployed at `program_id`
+pub fn append_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    data: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::Append { data }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::Migrate` instruction
+pub fn migrate(record_account: &Pubkey, signer: &Pubkey, payer: &Pubkey) -> Instruction {
+    migrate_with_program_id(&id(), record_account, signer, payer)
+}
+
+/// [`migrate`] for the program deployed at `program_id`
+pub fn migrate_with_program_id(
+    program_id: &Pubkey,
+    record_account: &Pubkey,
+    signer: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::Migrate.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ProposeMultiWrite` instruction
+pub fn propose_multi_write(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    offset: u64,
+    data: &[u8],
+    description: &[u8],
+) -> Instruction {
+    propose_multi_write_with_program_id(
+        &id(),
+        proposer,
+        proposal_account,
+        record_account,
+        multisig,
+        offset,
+        data,
+        description,
+    )
+}
+
+/// [`propose_multi_write`] for the program deployed at `program_id`
+#[allow(clippy::too_many_arguments)]
+pub fn propose_multi_write_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    offset: u64,
+    data: &[u8],
+    description: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ProposeMultiWrite {
+            offset,
+            data,
+            description,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ProposeMultiWriteIfMatch` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn propose_multi_write_if_match(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    offset: u64,
+    expected_hash: [u8; 32],
+    data: &[u8],
+    description: &[u8],
+) -> Instruction {
+    propose_multi_write_if_match_with_program_id(
+        &id(),
+        proposer,
+        proposal_account,
+        record_account,
+        multisig,
+        offset,
+        expected_hash,
+        data,
+        description,
+    )
+}
+
+/// [`propose_multi_write_if_match`] for the program deployed at `program_id`
+#[allow(clippy::too_many_arguments)]
+pub fn propose_multi_write_if_match_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    offset: u64,
+    expected_hash: [u8; 32],
+    data: &[u8],
+    description: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*record_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ProposeMultiWriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+            description,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ApproveProposal` instruction signed by a
+/// member key. For an unpause proposal, the target is the multisig config.
+pub fn approve_proposal(
+    approver: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    approve_proposal_with_program_id(&id(), approver, proposal_account, target_account, multisig)
+}
+
+/// [`approve_proposal`] for the program deployed at `program_id`
+pub fn approve_proposal_with_program_id(
+    program_id: &Pubkey,
+    approver: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*approver, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*target_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ApproveProposal.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ApproveProposal` instruction approving on
+/// behalf of a nested multisig, with its executed nested approval proposal
+pub fn approve_proposal_as_multisig(
+    child_multisig: &Pubkey,
+    child_proposal_account: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    approve_proposal_as_multisig_with_program_id(
+        &id(),
+        child_multisig,
+        child_proposal_account,
+        proposal_account,
+        target_account,
+        multisig,
+    )
+}
+
+/// [`approve_proposal_as_multisig`] for the program deployed at `program_id`
+pub fn approve_proposal_as_multisig_with_program_id(
+    program_id: &Pubkey,
+    child_multisig: &Pubkey,
+    child_proposal_account: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*child_multisig, false),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*target_account, false),
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new_readonly(*child_proposal_account, false),
+        ],
+        data: RecordInstruction::ApproveProposal.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ProposeNestedApproval` instruction
+pub fn propose_nested_approval(
+    proposer: &Pubkey,
+    child_proposal_account: &Pubkey,
+    parent_proposal_account: &Pubkey,
+    child_multisig: &Pubkey,
+    parent_multisig: &Pubkey,
+) -> Instruction {
+    propose_nested_approval_with_program_id(
+        &id(),
+        proposer,
+        child_proposal_account,
+        parent_proposal_account,
+        child_multisig,
+        parent_multisig,
+    )
+}
+
+/// [`propose_nested_approval`] for the program deployed at `program_id`
+pub fn propose_nested_approval_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    child_proposal_account: &Pubkey,
+    parent_proposal_account: &Pubkey,
+    child_multisig: &Pubkey,
+    parent_multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*child_proposal_account, false),
+            AccountMeta::new_readonly(*parent_proposal_account, false),
+            AccountMeta::new(*child_multisig, false),
+            AccountMeta::new_readonly(*parent_multisig, false),
+        ],
+        data: RecordInstruction::ProposeNestedApproval.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ApproveWithSignatures` instruction. The
+/// Ed25519 program instructions verifying the signatures must precede it in
+/// the transaction.
+pub fn approve_with_signatures(
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    approve_with_signatures_with_program_id(&id(), proposal_account, target_account, multisig)
+}
+
+/// [`approve_with_signatures`] for the program deployed at `program_id`
+pub fn approve_with_signatures_with_program_id(
+    program_id: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*target_account, false),
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+        data: RecordInstruction::ApproveWithSignatures.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ApproveWithEthSignature` instruction
+pub fn approve_with_eth_signature(
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+    recovery_id: u8,
+    signature: [u8; 64],
+) -> Instruction {
+    approve_with_eth_signature_with_program_id(
+        &id(),
+        proposal_account,
+        target_account,
+        multisig,
+        recovery_id,
+        signature,
+    )
+}
+
+/// [`approve_with_eth_signature`] for the program deployed at `program_id`
+pub fn approve_with_eth_signature_with_program_id(
+    program_id: &Pubkey,
+    proposal_account: &Pubkey,
+    target_account: &Pubkey,
+    multisig: &Pubkey,
+    recovery_id: u8,
+    signature: [u8; 64],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*target_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ApproveWithEthSignature {
+            recovery_id,
+            signature,
+        }
+        .pack(),
+    }
+}
+
+/// Create a `RecordInstruction::PauseMultisig` instruction
+pub fn pause_multisig(guardian: &Pubkey, multisig: &Pubkey) -> Instruction {
+    pause_multisig_with_program_id(&id(), guardian, multisig)
+}
+
+/// [`pause_multisig`] for the program deployed at `program_id`
+pub fn pause_multisig_with_program_id(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*guardian, true),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::PauseMultisig.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ProposeUnpauseMultisig` instruction
+pub fn propose_unpause_multisig(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    propose_unpause_multisig_with_program_id(&id(), proposer, proposal_account, multisig)
+}
+
+/// [`propose_unpause_multisig`] for the program deployed at `program_id`
+pub fn propose_unpause_multisig_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ProposeUnpauseMultisig.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::CloseProposal` instruction
+pub fn close_proposal(
+    member: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    close_proposal_with_program_id(&id(), member, proposal_account, multisig, receiver)
+}
+
+/// [`close_proposal`] for the program deployed at `program_id`
+pub fn close_proposal_with_program_id(
+    program_id: &Pubkey,
+    member: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    receiver: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*member, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(*receiver, false),
+        ],
+        data: RecordInstruction::CloseProposal.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::CreateProposalBuffer` instruction
+pub fn create_proposal_buffer(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    description: &[u8],
+) -> Instruction {
+    create_proposal_buffer_with_program_id(
+        &id(),
+        proposer,
+        proposal_account,
+        record_account,
+        multisig,
+        description,
+    )
+}
+
+/// [`create_proposal_buffer`] for the program deployed at `program_id`
+pub fn create_proposal_buffer_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    description: &[u8],
 ) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id: *program_id,
         accounts: vec![
-            AccountMeta::new(*record_account, false),
-            AccountMeta::new_readonly(*signer, true),
-            AccountMeta::new_readonly(*new_authority, false),
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new_readonly(*record_account, false),
+            AccountMeta::new(*multisig, false),
         ],
-        data: RecordInstruction::SetAuthority.pack(),
+        data: RecordInstruction::CreateProposalBuffer { description }.pack(),
     }
 }
 
-/// Create a `RecordInstruction::CloseAccount` instruction
-pub fn close_account(record_account: &Pubkey, signer: &Pubkey, receiver: &Pubkey) -> Instruction {
+/// Create a `RecordInstruction::WriteProposalBuffer` instruction
+pub fn write_proposal_buffer(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    write_proposal_buffer_with_program_id(&id(), proposer, proposal_account, offset, data)
+}
+
+/// [`write_proposal_buffer`] for the program deployed at `program_id`
+pub fn write_proposal_buffer_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    offset: u64,
+    data: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+        ],
+        data: RecordInstruction::WriteProposalBuffer { offset, data }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::FinalizeProposalBuffer` instruction
+pub fn finalize_proposal_buffer(proposer: &Pubkey, proposal_account: &Pubkey) -> Instruction {
+    finalize_proposal_buffer_with_program_id(&id(), proposer, proposal_account)
+}
+
+/// [`finalize_proposal_buffer`] for the program deployed at `program_id`
+pub fn finalize_proposal_buffer_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+        ],
+        data: RecordInstruction::FinalizeProposalBuffer.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::MigrateMultisig` instruction
+pub fn migrate_multisig(multisig: &Pubkey, payer: &Pubkey, signers: &[&Pubkey]) -> Instruction {
+    migrate_multisig_with_program_id(&id(), multisig, payer, signers)
+}
+
+/// [`migrate_multisig`] for the program deployed at `program_id`
+pub fn migrate_multisig_with_program_id(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    payer: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*multisig, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+    ];
+    accounts.extend(
+        signers
+            .iter()
+            .map(|signer| AccountMeta::new_readonly(**signer, true)),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: RecordInstruction::MigrateMultisig.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::MigrateProposal` instruction
+pub fn migrate_proposal(
+    member: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    migrate_proposal_with_program_id(&id(), member, proposal_account, multisig, payer)
+}
+
+/// [`migrate_proposal`] for the program deployed at `program_id`
+pub fn migrate_proposal_with_program_id(
+    program_id: &Pubkey,
+    member: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*member, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+        ],
+        data: RecordInstruction::MigrateProposal.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::PauseRecord` instruction
+pub fn pause_record(guardian: &Pubkey, record_account: &Pubkey, multisig: &Pubkey) -> Instruction {
+    pause_record_with_program_id(&id(), guardian, record_account, multisig)
+}
+
+/// [`pause_record`] for the program deployed at `program_id`
+pub fn pause_record_with_program_id(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id: *program_id,
         accounts: vec![
+            AccountMeta::new_readonly(*guardian, true),
             AccountMeta::new(*record_account, false),
-            AccountMeta::new_readonly(*signer, true),
-            AccountMeta::new(*receiver, false),
+            AccountMeta::new_readonly(*multisig, false),
         ],
-        data: RecordInstruction::CloseAccount.pack(),
+        data: RecordInstruction::PauseRecord.pack(),
     }
 }
 
-/// Create a `RecordInstruction::Reallocate` instruction
-pub fn reallocate(record_account: &Pubkey, signer: &Pubkey, data_length: u64) -> Instruction {
+/// Create a `RecordInstruction::ProposeUnpauseRecord` instruction
+pub fn propose_unpause_record(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    description: &[u8],
+) -> Instruction {
+    propose_unpause_record_with_program_id(
+        &id(),
+        proposer,
+        proposal_account,
+        record_account,
+        multisig,
+        description,
+    )
+}
+
+/// [`propose_unpause_record`] for the program deployed at `program_id`
+pub fn propose_unpause_record_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    record_account: &Pubkey,
+    multisig: &Pubkey,
+    description: &[u8],
+) -> Instruction {
     Instruction {
-        program_id: id(),
+        program_id: *program_id,
         accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
             AccountMeta::new(*record_account, false),
-            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new(*multisig, false),
         ],
-        data: RecordInstruction::Reallocate { data_length }.pack(),
+        data: RecordInstruction::ProposeUnpauseRecord { description }.pack(),
+    }
+}
+
+/// Create a `RecordInstruction::ProposeSetGuardian` instruction
+pub fn propose_set_guardian(
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    guardian: &Pubkey,
+    description: &[u8],
+) -> Instruction {
+    propose_set_guardian_with_program_id(
+        &id(),
+        proposer,
+        proposal_account,
+        multisig,
+        guardian,
+        description,
+    )
+}
+
+/// [`propose_set_guardian`] for the program deployed at `program_id`
+pub fn propose_set_guardian_with_program_id(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    proposal_account: &Pubkey,
+    multisig: &Pubkey,
+    guardian: &Pubkey,
+    description: &[u8],
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*proposer, true),
+            AccountMeta::new(*proposal_account, false),
+            AccountMeta::new(*multisig, false),
+        ],
+        data: RecordInstruction::ProposeSetGuardian {
+            guardian: *guardian,
+            description,
+        }
+        .pack(),
     }
 }
 
@@ -322,11 +2268,436 @@ mod tests {
         assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
     }
 
+    #[test]
+    fn serialize_propose_multi_write() {
+        let data = &TEST_BYTES;
+        let offset = 4u64;
+        let instruction = RecordInstruction::ProposeMultiWrite {
+            offset,
+            data,
+            description: &[],
+        };
+        let mut expected = vec![5];
+        expected.extend_from_slice(&offset.to_le_bytes());
+        expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        expected.extend_from_slice(data);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        let description = b"Rotate the feed key, see issue #42";
+        let instruction = RecordInstruction::ProposeMultiWrite {
+            offset,
+            data,
+            description,
+        };
+        expected.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        expected.extend_from_slice(description);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        expected.pop();
+        assert_eq!(
+            RecordInstruction::unpack(&expected).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+
+    #[test]
+    fn serialize_propose_nested_approval() {
+        let instruction = RecordInstruction::ProposeNestedApproval;
+        let expected = vec![7];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_approve_with_signatures() {
+        let instruction = RecordInstruction::ApproveWithSignatures;
+        let expected = vec![8];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_approve_with_eth_signature() {
+        let recovery_id = 1u8;
+        let signature = [7u8; 64];
+        let instruction = RecordInstruction::ApproveWithEthSignature {
+            recovery_id,
+            signature,
+        };
+        let mut expected = vec![9, recovery_id];
+        expected.extend_from_slice(&signature);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_pause_multisig() {
+        let instruction = RecordInstruction::PauseMultisig;
+        let expected = vec![10];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_propose_unpause_multisig() {
+        let instruction = RecordInstruction::ProposeUnpauseMultisig;
+        let expected = vec![11];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_close_proposal() {
+        let instruction = RecordInstruction::CloseProposal;
+        let expected = vec![12];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_create_proposal_buffer() {
+        let description = b"Rewrite the whole feed";
+        let instruction = RecordInstruction::CreateProposalBuffer { description };
+        let mut expected = vec![13];
+        expected.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        expected.extend_from_slice(description);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        let instruction = RecordInstruction::CreateProposalBuffer { description: &[] };
+        let expected = vec![13];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_write_proposal_buffer() {
+        let data = &TEST_BYTES;
+        let offset = 1024u64;
+        let instruction = RecordInstruction::WriteProposalBuffer { offset, data };
+        let mut expected = vec![14];
+        expected.extend_from_slice(&offset.to_le_bytes());
+        expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        expected.extend_from_slice(data);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        expected.pop();
+        assert_eq!(
+            RecordInstruction::unpack(&expected).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+
+    #[test]
+    fn serialize_finalize_proposal_buffer() {
+        let instruction = RecordInstruction::FinalizeProposalBuffer;
+        let expected = vec![15];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_append() {
+        let data = &TEST_BYTES;
+        let instruction = RecordInstruction::Append { data };
+        let mut expected = vec![16];
+        expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        expected.extend_from_slice(data);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_write_if_match() {
+        let data = &TEST_BYTES;
+        let offset = 8u64;
+        let expected_hash = [3u8; 32];
+        let instruction = RecordInstruction::WriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+        };
+        let mut expected = vec![17];
+        expected.extend_from_slice(&offset.to_le_bytes());
+        expected.extend_from_slice(&expected_hash);
+        expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        expected.extend_from_slice(data);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_propose_multi_write_if_match() {
+        let data = &TEST_BYTES;
+        let offset = 8u64;
+        let expected_hash = [3u8; 32];
+        let description = b"Only if nobody changed it";
+        let instruction = RecordInstruction::ProposeMultiWriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+            description,
+        };
+        let mut expected = vec![18];
+        expected.extend_from_slice(&offset.to_le_bytes());
+        expected.extend_from_slice(&expected_hash);
+        expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        expected.extend_from_slice(data);
+        expected.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        expected.extend_from_slice(description);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_migrate() {
+        let instruction = RecordInstruction::Migrate;
+        let expected = vec![19];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_write_batch() {
+        let segments = vec![(0u64, &TEST_BYTES[..]), (16u64, &[1u8, 2, 3][..])];
+        let instruction = RecordInstruction::WriteBatch {
+            segments: segments.clone(),
+        };
+        let mut expected = vec![20];
+        expected.extend_from_slice(&2u16.to_le_bytes());
+        for (offset, data) in &segments {
+            expected.extend_from_slice(&offset.to_le_bytes());
+            expected.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            expected.extend_from_slice(data);
+        }
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        // a missing segment
+        let err = RecordInstruction::unpack(&expected[..expected.len() - 15]).unwrap_err();
+        assert_eq!(err, ProgramError::InvalidInstructionData);
+    }
+
+    #[test]
+    fn serialize_create_and_initialize() {
+        let data_length = 16u64;
+        let seeds = vec![&b"record"[..], &TEST_BYTES[..], &[255u8][..]];
+        let instruction = RecordInstruction::CreateAndInitialize {
+            data_length,
+            seeds: seeds.clone(),
+        };
+        let mut expected = vec![21];
+        expected.extend_from_slice(&data_length.to_le_bytes());
+        expected.push(seeds.len() as u8);
+        for seed in &seeds {
+            expected.push(seed.len() as u8);
+            expected.extend_from_slice(seed);
+        }
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        // a truncated seed
+        let err = RecordInstruction::unpack(&expected[..expected.len() - 1]).unwrap_err();
+        assert_eq!(err, ProgramError::InvalidInstructionData);
+    }
+
+    #[test]
+    fn serialize_freeze() {
+        let instruction = RecordInstruction::Freeze;
+        let expected = vec![22];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_authority_nomination() {
+        for (instruction, tag) in [
+            (RecordInstruction::NominateAuthority, 23),
+            (RecordInstruction::AcceptAuthority, 24),
+            (RecordInstruction::CancelAuthorityNomination, 25),
+        ] {
+            let expected = vec![tag];
+            assert_eq!(instruction.pack(), expected);
+            assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn serialize_grant_delegation() {
+        let instruction = RecordInstruction::GrantDelegation {
+            start: 8,
+            end: 16,
+            expiry_slot: 1234,
+        };
+        let mut expected = vec![26];
+        expected.extend_from_slice(&8u64.to_le_bytes());
+        expected.extend_from_slice(&16u64.to_le_bytes());
+        expected.extend_from_slice(&1234u64.to_le_bytes());
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        expected.pop();
+        assert_eq!(
+            RecordInstruction::unpack(&expected).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+
+    #[test]
+    fn serialize_revoke_delegation() {
+        let instruction = RecordInstruction::RevokeDelegation;
+        let expected = vec![27];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_access_list() {
+        for (instruction, tag) in [
+            (RecordInstruction::AddWriter, 28),
+            (RecordInstruction::RemoveWriter, 29),
+        ] {
+            let expected = vec![tag];
+            assert_eq!(instruction.pack(), expected);
+            assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn serialize_multisig_migrations() {
+        for (instruction, tag) in [
+            (RecordInstruction::MigrateMultisig, 30),
+            (RecordInstruction::MigrateProposal, 31),
+        ] {
+            let expected = vec![tag];
+            assert_eq!(instruction.pack(), expected);
+            assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn serialize_pause_record() {
+        let instruction = RecordInstruction::PauseRecord;
+        let expected = vec![32];
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
+    #[test]
+    fn serialize_propose_unpause_record() {
+        let description = b"Signer key rotated";
+        let instruction = RecordInstruction::ProposeUnpauseRecord { description };
+        let mut expected = vec![33];
+        expected.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        expected.extend_from_slice(description);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+
+        // the payload of an executed proposal is read back zero padded
+        let instruction = RecordInstruction::ProposeUnpauseRecord { description: &[] };
+        assert_eq!(instruction.pack(), vec![33]);
+        assert_eq!(
+            RecordInstruction::unpack(&[33, 0, 0, 0]).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_propose_set_guardian() {
+        let guardian = Pubkey::new_from_array([3; 32]);
+        let description = b"New incident responder";
+        let instruction = RecordInstruction::ProposeSetGuardian {
+            guardian,
+            description,
+        };
+        let mut expected = vec![34];
+        expected.extend_from_slice(guardian.as_ref());
+        expected.extend_from_slice(&(description.len() as u16).to_le_bytes());
+        expected.extend_from_slice(description);
+        assert_eq!(instruction.pack(), expected);
+        assert_eq!(RecordInstruction::unpack(&expected).unwrap(), instruction);
+    }
+
     #[test]
     fn deserialize_invalid_instruction() {
-        let mut expected = vec![12];
+        let mut expected = vec![u8::MAX];
         expected.extend_from_slice(&TEST_BYTES);
         let err: ProgramError = RecordInstruction::unpack(&expected).unwrap_err();
         assert_eq!(err, ProgramError::InvalidInstructionData);
     }
+
+    #[test]
+    fn deserialize_truncated_instruction() {
+        let instruction = RecordInstruction::WriteIfMatch {
+            offset: 4,
+            expected_hash: [2; 32],
+            data: &TEST_BYTES,
+        };
+        let packed = instruction.pack();
+        for len in 0..packed.len() {
+            assert_eq!(
+                RecordInstruction::unpack(&packed[..len]).unwrap_err(),
+                ProgramError::InvalidInstructionData
+            );
+        }
+
+        // a length larger than the data that follows
+        let mut lying = vec![1];
+        lying.extend_from_slice(&0u64.to_le_bytes());
+        lying.extend_from_slice(&u32::MAX.to_le_bytes());
+        lying.extend_from_slice(&TEST_BYTES);
+        assert_eq!(
+            RecordInstruction::unpack(&lying).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+
+        // a segment count larger than the segments that follow
+        let mut lying = vec![20];
+        lying.extend_from_slice(&u16::MAX.to_le_bytes());
+        assert_eq!(
+            RecordInstruction::unpack(&lying).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
+
+    #[test]
+    fn deserialize_trailing_bytes() {
+        for instruction in [
+            RecordInstruction::Initialize,
+            RecordInstruction::Write {
+                offset: 4,
+                data: &TEST_BYTES,
+            },
+            RecordInstruction::GrantDelegation {
+                start: 1,
+                end: 2,
+                expiry_slot: 3,
+            },
+        ] {
+            let mut packed = instruction.pack();
+            packed.push(0);
+            assert_eq!(
+                RecordInstruction::unpack(&packed).unwrap_err(),
+                ProgramError::InvalidInstructionData
+            );
+        }
+
+        // proposal payloads are read back from zero-padded accounts
+        let instruction = RecordInstruction::ProposeMultiWrite {
+            offset: 4,
+            data: &TEST_BYTES,
+            description: b"description",
+        };
+        let mut packed = instruction.pack();
+        packed.extend_from_slice(&[0; 16]);
+        assert_eq!(RecordInstruction::unpack(&packed).unwrap(), instruction);
+        packed.push(1);
+        assert_eq!(
+            RecordInstruction::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidInstructionData
+        );
+    }
 }
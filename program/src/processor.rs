@@ -1,18 +1,51 @@
 //! Program state processor
 
-use std::mem::size_of;
 use multisig::instructions::*;
 
 use {
-    crate::{error::RecordError, instruction::RecordInstruction, state::RecordData},
+    crate::{
+        error::RecordError,
+        event::RecordEvent,
+        instruction::RecordInstruction,
+        state::{AccessList, Delegation, RecordData, RecordDataV1},
+    },
+    multisig::config::MultisigConfig,
     solana_account_info::{next_account_info, AccountInfo},
+    solana_clock::Clock,
+    solana_cpi::{invoke, invoke_signed},
     solana_msg::msg,
     solana_program_error::{ProgramError, ProgramResult},
     solana_program_pack::IsInitialized,
-    solana_pubkey::Pubkey,
+    solana_pubkey::{Pubkey, MAX_SEEDS},
+    solana_rent::Rent,
+    solana_system_interface::instruction as system_instruction,
+    solana_sysvar::Sysvar,
 };
 
+/// checks that an account holding program state is owned by the program, so
+/// that the same build serves any deployment address
+fn check_owner(account_info: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account_info.owner != program_id {
+        msg!("Account {} not owned by the program", account_info.key);
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
+
+/// checks the owner of the accounts at `indices`, those holding program
+/// state. Missing accounts are left to the instruction to report.
+fn check_owners(program_id: &Pubkey, accounts: &[AccountInfo], indices: &[usize]) -> ProgramResult {
+    for account_info in indices.iter().filter_map(|index| accounts.get(*index)) {
+        check_owner(account_info, program_id)?;
+    }
+    Ok(())
+}
+
 fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) -> ProgramResult {
+    if *expected_authority == RecordData::IMMUTABLE_AUTHORITY {
+        msg!("Record authority was renounced");
+        return Err(RecordError::Immutable.into());
+    }
     if expected_authority != authority_info.key {
         msg!("Incorrect record authority provided");
         return Err(RecordError::IncorrectAuthority.into());
@@ -24,56 +57,518 @@ fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) ->
     Ok(())
 }
 
+/// paused records only take the unpause proposal of their multisig
+fn check_not_paused(account_data: &RecordData) -> ProgramResult {
+    if account_data.is_paused() {
+        msg!("Record is paused");
+        return Err(RecordError::Paused.into());
+    }
+    Ok(())
+}
+
+/// checks that the signer may write `start..end` of the record data, either
+/// as its authority or through the delegation or access list account, when
+/// given. Which of the two it is follows from its address, never its data.
+fn check_writer(
+    program_id: &Pubkey,
+    data_info: &AccountInfo,
+    account_data: &RecordData,
+    writer_info: &AccountInfo,
+    delegation_info: Option<&AccountInfo>,
+    start: u64,
+    end: u64,
+) -> ProgramResult {
+    let Some(delegation_info) = delegation_info else {
+        return check_authority(writer_info, &account_data.authority);
+    };
+    if account_data.is_immutable() {
+        msg!("Record authority was renounced");
+        return Err(RecordError::Immutable.into());
+    }
+    check_owner(delegation_info, program_id)?;
+    let (access_list_key, _) = AccessList::find_address(data_info.key, program_id);
+    if access_list_key == *delegation_info.key {
+        let access_list = AccessList::unpack(&delegation_info.data.borrow())?;
+        if access_list.record != *data_info.key || !access_list.is_writer(writer_info.key) {
+            msg!("Signer is not a listed writer of the record");
+            return Err(RecordError::NotAListedWriter.into());
+        }
+        if !writer_info.is_signer {
+            msg!("Record writer signature missing");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        return Ok(());
+    }
+    let (delegation_key, _) = Delegation::find_address(data_info.key, writer_info.key, program_id);
+    if delegation_key != *delegation_info.key {
+        msg!("Account is neither the delegation of the signer nor the access list of the record");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let delegation = Delegation::unpack(&delegation_info.data.borrow())?;
+    if delegation.record != *data_info.key || delegation.delegate != *writer_info.key {
+        msg!("Delegation does not match the record and signer");
+        return Err(RecordError::DelegationMismatch.into());
+    }
+    if !writer_info.is_signer {
+        msg!("Record delegate signature missing");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if delegation.is_expired(Clock::get()?.slot) {
+        msg!("Delegation expired at slot {}", { delegation.expiry_slot });
+        return Err(RecordError::DelegationExpired.into());
+    }
+    if !delegation.covers(start, end) {
+        msg!(
+            "Write {}..{} outside of the delegated range {}..{}",
+            start,
+            end,
+            { delegation.start },
+            { delegation.end }
+        );
+        return Err(RecordError::OutsideDelegatedRange.into());
+    }
+    Ok(())
+}
+
+/// seeds signing for a record created at a program address: the caller's
+/// seeds behind [`RecordData::SEED_PREFIX`], so that no record can be created
+/// at the address of a delegation or access list
+fn record_seeds<'a>(seeds: &[&'a [u8]]) -> Result<Vec<&'a [u8]>, ProgramError> {
+    if seeds.len() >= MAX_SEEDS {
+        msg!("Too many record seeds, at most {}", MAX_SEEDS - 1);
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+    if seeds
+        .first()
+        .is_some_and(|seed| [Delegation::SEED_PREFIX, AccessList::SEED_PREFIX].contains(seed))
+    {
+        msg!("Record seeds cannot start with a reserved seed prefix");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut prefixed = vec![RecordData::SEED_PREFIX];
+    prefixed.extend_from_slice(seeds);
+    Ok(prefixed)
+}
+
+/// checks that the record contents about to be replaced are the expected ones
+fn check_contents(contents: &[u8], expected_hash: &[u8; 32]) -> ProgramResult {
+    if RecordData::hash_contents(contents) != *expected_hash {
+        msg!("Record contents changed since the write was prepared");
+        return Err(RecordError::ContentMismatch.into());
+    }
+    Ok(())
+}
+
+/// grows the record, multisig config or proposal account to `new_len`, the
+/// payer topping up the lamports needed to stay rent exempt
+fn resize_rent_exempt<'a>(
+    data_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    msg!(
+        "reallocating +{:?} bytes",
+        new_len.saturating_sub(data_info.data_len()),
+    );
+    data_info.resize(new_len)?;
+
+    let rent_lamports = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(data_info.lamports());
+    if rent_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, data_info.key, rent_lamports),
+            &[
+                payer_info.clone(),
+                data_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// creates the program owned account at the program address of `seeds`.
+/// Anyone can send lamports to the address beforehand, which
+/// `create_account` rejects, so a funded address is only topped up to rent
+/// exemption before being allocated and assigned.
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    pda_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    if pda_info.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                pda_info.key,
+                rent_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                pda_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[seeds],
+        );
+    }
+
+    let shortfall = rent_lamports.saturating_sub(pda_info.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, pda_info.key, shortfall),
+            &[
+                payer_info.clone(),
+                pda_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(pda_info.key, space as u64),
+        &[pda_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(pda_info.key, program_id),
+        &[pda_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )
+}
+
+/// writes `data` at `offset` of the record, if given only when the replaced
+/// contents match `expected_hash`
+fn process_write(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    offset: u64,
+    data: &[u8],
+    expected_hash: Option<&[u8; 32]>,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let data_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let delegation_info = next_account_info(account_info_iter).ok();
+    check_owner(data_info, program_id)?;
+    let raw_data = &mut data_info.data.borrow_mut();
+    let mut account_data = RecordData::unpack(raw_data)?;
+    if !account_data.is_initialized() {
+        msg!("Record account not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    check_not_paused(&account_data)?;
+    check_writer(
+        program_id,
+        data_info,
+        &account_data,
+        authority_info,
+        delegation_info,
+        offset,
+        offset.saturating_add(data.len() as u64),
+    )?;
+    let start = account_data
+        .writable_start_index()
+        .saturating_add(offset as usize);
+    let end = start.saturating_add(data.len());
+    if end > raw_data.len() {
+        msg!("Write at offset {} does not fit the record", offset);
+        return Err(RecordError::OutOfBounds.into());
+    }
+    if let Some(expected_hash) = expected_hash {
+        check_contents(&raw_data[start..end], expected_hash)?;
+    }
+    raw_data[start..end].copy_from_slice(data);
+    account_data.extend_length(end - account_data.writable_start_index());
+    account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+    account_data.pack_into(raw_data)?;
+    RecordEvent::RecordWritten {
+        record: *data_info.key,
+        writer: *authority_info.key,
+        sequence: account_data.sequence,
+        offset,
+        len: data.len() as u64,
+        hash: RecordData::hash_contents(data),
+    }
+    .emit();
+    Ok(())
+}
+
+/// writes every segment of a batch, after checking all of them fit the record
+fn process_write_batch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    segments: &[(u64, &[u8])],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let data_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    check_owner(data_info, program_id)?;
+    let raw_data = &mut data_info.data.borrow_mut();
+    let mut account_data = RecordData::unpack(raw_data)?;
+    if !account_data.is_initialized() {
+        msg!("Record account not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    check_authority(authority_info, &account_data.authority)?;
+    check_not_paused(&account_data)?;
+
+    let writable_start_index = account_data.writable_start_index();
+    let ranges = segments
+        .iter()
+        .map(|(offset, data)| {
+            let start = usize::try_from(*offset)
+                .ok()
+                .and_then(|offset| offset.checked_add(writable_start_index))
+                .ok_or(RecordError::Overflow)?;
+            let end = start.checked_add(data.len()).ok_or(RecordError::Overflow)?;
+            if end > raw_data.len() {
+                msg!("Segment at offset {} does not fit the record", offset);
+                return Err(RecordError::OutOfBounds.into());
+            }
+            Ok(start..end)
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()?;
+
+    for (range, (_, data)) in ranges.into_iter().zip(segments) {
+        account_data.extend_length(range.end - writable_start_index);
+        raw_data[range].copy_from_slice(data);
+    }
+    account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+    account_data.pack_into(raw_data)?;
+    for (offset, data) in segments {
+        RecordEvent::RecordWritten {
+            record: *data_info.key,
+            writer: *authority_info.key,
+            sequence: account_data.sequence,
+            offset: *offset,
+            len: data.len() as u64,
+            hash: RecordData::hash_contents(data),
+        }
+        .emit();
+    }
+    Ok(())
+}
+
+/// checks that the record header can count its delegation and access list
+/// accounts, which v1 headers have no room for
+fn check_grants_supported(account_data: &RecordData) -> ProgramResult {
+    if account_data.version != RecordData::CURRENT_VERSION {
+        msg!(
+            "Record version {} cannot count its grants",
+            account_data.version
+        );
+        return Err(RecordError::UnsupportedVersion.into());
+    }
+    Ok(())
+}
+
+/// hands a delegation or access list account back to the system program,
+/// draining its lamports to `destination_info`
+fn close_grant_account(grant_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+    if destination_info.key == grant_info.key {
+        msg!("Cannot close an account into itself");
+        return Err(RecordError::CloseIntoSelf.into());
+    }
+
+    let destination_starting_lamports = destination_info.lamports();
+    let grant_lamports = grant_info.lamports();
+    **grant_info.lamports.borrow_mut() = 0;
+    **destination_info.lamports.borrow_mut() = destination_starting_lamports
+        .checked_add(grant_lamports)
+        .ok_or(RecordError::Overflow)?;
+
+    grant_info.data.borrow_mut().fill(0);
+    grant_info.resize(0)?;
+    grant_info.assign(&solana_system_interface::program::id());
+    Ok(())
+}
+
+/// adds or removes the writer of an access list, creating the access list
+/// account on the first addition and closing it with the last removal
+fn process_update_access_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    adding: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let data_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let writer_info = next_account_info(account_info_iter)?;
+    let access_list_info = next_account_info(account_info_iter)?;
+
+    check_owner(data_info, program_id)?;
+    let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+    if !account_data.is_initialized() {
+        msg!("Record account not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    check_authority(authority_info, &account_data.authority)?;
+    check_not_paused(&account_data)?;
+    check_grants_supported(&account_data)?;
+
+    let (access_list_key, bump) = AccessList::find_address(data_info.key, program_id);
+    if access_list_key != *access_list_info.key {
+        msg!("Access list account does not match the record");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut access_list = if access_list_info.data_is_empty() {
+        if !adding {
+            msg!("Writer is not listed");
+            return Err(RecordError::WriterNotListed.into());
+        }
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        create_pda_account(
+            program_id,
+            access_list_info,
+            payer_info,
+            system_program_info,
+            AccessList::SIZE,
+            &[AccessList::SEED_PREFIX, data_info.key.as_ref(), &[bump]],
+        )?;
+        account_data.open_grant()?;
+        account_data.pack_into(&mut data_info.data.borrow_mut())?;
+        AccessList::new(*data_info.key)
+    } else {
+        check_owner(access_list_info, program_id)?;
+        AccessList::unpack(&access_list_info.data.borrow())?
+    };
+
+    let event = if adding {
+        access_list.add_writer(writer_info.key)?;
+        RecordEvent::WriterAdded {
+            record: *data_info.key,
+            writer: *writer_info.key,
+        }
+    } else {
+        access_list.remove_writer(writer_info.key)?;
+        RecordEvent::WriterRemoved {
+            record: *data_info.key,
+            writer: *writer_info.key,
+        }
+    };
+    if access_list.writers().next().is_none() {
+        let destination_info = next_account_info(account_info_iter)?;
+        close_grant_account(access_list_info, destination_info)?;
+        account_data.close_grant()?;
+        account_data.pack_into(&mut data_info.data.borrow_mut())?;
+    } else {
+        access_list_info
+            .data
+            .borrow_mut()
+            .copy_from_slice(bytemuck::bytes_of(&access_list));
+    }
+    event.emit();
+    Ok(())
+}
+
 /// callback for multisig
 /// This should have only instruction-related logic
 pub fn multisig_handler(
+    program_id: &Pubkey,
     proposal_data: &[u8],
     client_account: &AccountInfo,
     multisig_key: &Pubkey,
 ) -> ProgramResult {
-    // client_account ownership needs to be verified
-    // if *client_account.owner != crate::id() {
-    //     msg!("Client account is not owned by this program! {}, {}", *client_account.owner, id());
-    //     return Err(ProgramError::IllegalOwner);
-    // }
+    check_owner(client_account, program_id)?;
 
     let dest_data = &mut client_account.try_borrow_mut_data()?;
 
-    let (offset, data_to_write): (usize, &[u8]) = match RecordInstruction::unpack(proposal_data) {
-        Ok(RecordInstruction::ProposeMultiWrite { offset, data }) => (offset as usize, data),
-        _ => return Err(ProgramError::InvalidAccountData),
+    // an unpause proposal carries no write
+    let write = match RecordInstruction::unpack(proposal_data) {
+        Ok(RecordInstruction::ProposeMultiWrite { offset, data, .. }) => Some((offset, data, None)),
+        Ok(RecordInstruction::ProposeMultiWriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+            ..
+        }) => Some((offset, data, Some(expected_hash))),
+        Ok(RecordInstruction::ProposeUnpauseRecord { .. }) => None,
+        _ => {
+            msg!("Proposal payload is not a record write or unpause");
+            return Err(RecordError::InvalidProposalPayload.into());
+        }
     };
 
-    let start = offset + RecordData::WRITABLE_START_INDEX;
-    let end = start + data_to_write.len();
-
-    if end > dest_data.len() {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let header_meta = bytemuck::from_bytes::<RecordData>(&dest_data[..size_of::<RecordData>()]);
+    let mut header_meta = RecordData::unpack(dest_data)?;
 
     if !header_meta.is_initialized() {
         return Err(ProgramError::UninitializedAccount);
     }
 
+    if header_meta.is_immutable() {
+        return Err(RecordError::Immutable.into());
+    }
+
     if header_meta.authority != *multisig_key {
-        return Err(ProgramError::IllegalOwner);
+        msg!("Record is not controlled by the multisig");
+        return Err(RecordError::IncorrectAuthority.into());
     }
 
-    dest_data[start..end].copy_from_slice(data_to_write);
+    let Some((offset, data_to_write, expected_hash)) = write else {
+        header_meta.paused = 0;
+        header_meta.record_mutation(multisig_key, Clock::get()?.slot)?;
+        header_meta.pack_into(dest_data)?;
+        RecordEvent::RecordUnpaused {
+            record: *client_account.key,
+        }
+        .emit();
+        return Ok(());
+    };
+    check_not_paused(&header_meta)?;
 
+    let writable_start_index = header_meta.writable_start_index();
+    let start = usize::try_from(offset)
+        .ok()
+        .and_then(|offset| offset.checked_add(writable_start_index))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let end = start
+        .checked_add(data_to_write.len())
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    if end > dest_data.len() {
+        msg!("Write at offset {} does not fit the record", offset);
+        return Err(RecordError::OutOfBounds.into());
+    }
+
+    if let Some(expected_hash) = expected_hash {
+        check_contents(&dest_data[start..end], &expected_hash)?;
+    }
+
+    dest_data[start..end].copy_from_slice(data_to_write);
+    header_meta.extend_length(end - writable_start_index);
+    header_meta.record_mutation(multisig_key, Clock::get()?.slot)?;
+    header_meta.pack_into(dest_data)?;
+    RecordEvent::RecordWritten {
+        record: *client_account.key,
+        writer: *multisig_key,
+        sequence: header_meta.sequence,
+        offset,
+        len: data_to_write.len() as u64,
+        hash: RecordData::hash_contents(data_to_write),
+    }
+    .emit();
     Ok(())
 }
 
 /// Instruction processor
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     input: &[u8],
 ) -> ProgramResult {
     let instruction = RecordInstruction::unpack(input)?;
     let account_info_iter = &mut accounts.iter();
+    let handler = |proposal_data: &[u8], client_account: &AccountInfo, multisig_key: &Pubkey| {
+        multisig_handler(program_id, proposal_data, client_account, multisig_key)
+    };
 
     match instruction {
         RecordInstruction::Initialize => {
@@ -82,52 +577,95 @@ pub fn process_instruction(
             let data_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
 
+            check_owner(data_info, program_id)?;
+
             let raw_data = &mut data_info.data.borrow_mut();
             if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
+                msg!("Record account is too small for the record header");
+                return Err(RecordError::RecordTooSmall.into());
             }
 
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
-            )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+            let account_data = RecordData::unpack(raw_data)?;
             if account_data.is_initialized() {
                 msg!("Record account already initialized");
                 return Err(ProgramError::AccountAlreadyInitialized);
             }
 
-            account_data.authority = *authority_info.key;
-            account_data.version = RecordData::CURRENT_VERSION;
+            RecordData::new(*authority_info.key, Clock::get()?.slot).pack_into(raw_data)?;
+            RecordEvent::RecordInitialized {
+                record: *data_info.key,
+                authority: *authority_info.key,
+            }
+            .emit();
             Ok(())
         }
 
-        RecordInstruction::Write { offset, data } => {
-            msg!("RecordInstruction::Write");
+        RecordInstruction::CreateAndInitialize { data_length, seeds } => {
+            msg!("RecordInstruction::CreateAndInitialize");
             let data_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
-            {
-                let raw_data = &data_info.data.borrow();
-                if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                    return Err(ProgramError::InvalidAccountData);
-                }
-                let account_data = bytemuck::try_from_bytes::<RecordData>(
-                    &raw_data[..RecordData::WRITABLE_START_INDEX],
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                if !account_data.is_initialized() {
-                    msg!("Record account not initialized");
-                    return Err(ProgramError::UninitializedAccount);
-                }
-                check_authority(authority_info, &account_data.authority)?;
-            }
-            let start = RecordData::WRITABLE_START_INDEX.saturating_add(offset as usize);
-            let end = start.saturating_add(data.len());
-            if end > data_info.data.borrow().len() {
-                Err(ProgramError::AccountDataTooSmall)
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+
+            let account_length = usize::try_from(data_length)
+                .ok()
+                .and_then(|data_length| data_length.checked_add(RecordData::WRITABLE_START_INDEX))
+                .ok_or(RecordError::Overflow)?;
+            if seeds.is_empty() {
+                invoke(
+                    &system_instruction::create_account(
+                        payer_info.key,
+                        data_info.key,
+                        Rent::get()?.minimum_balance(account_length),
+                        account_length as u64,
+                        program_id,
+                    ),
+                    &[
+                        payer_info.clone(),
+                        data_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
             } else {
-                data_info.data.borrow_mut()[start..end].copy_from_slice(data);
-                Ok(())
+                // the runtime only signs for the record if the seeds derive its address
+                let seeds = record_seeds(&seeds)?;
+                create_pda_account(
+                    program_id,
+                    data_info,
+                    payer_info,
+                    system_program_info,
+                    account_length,
+                    &seeds,
+                )?;
             }
+
+            RecordData::new(*authority_info.key, Clock::get()?.slot)
+                .pack_into(&mut data_info.data.borrow_mut())?;
+            RecordEvent::RecordInitialized {
+                record: *data_info.key,
+                authority: *authority_info.key,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::Write { offset, data } => {
+            msg!("RecordInstruction::Write");
+            process_write(program_id, accounts, offset, data, None)
+        }
+
+        RecordInstruction::WriteBatch { segments } => {
+            msg!("RecordInstruction::WriteBatch");
+            process_write_batch(program_id, accounts, &segments)
+        }
+
+        RecordInstruction::WriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+        } => {
+            msg!("RecordInstruction::WriteIfMatch");
+            process_write(program_id, accounts, offset, data, Some(&expected_hash))
         }
 
         RecordInstruction::SetAuthority => {
@@ -135,47 +673,296 @@ pub fn process_instruction(
             let data_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
             let new_authority_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
             let raw_data = &mut data_info.data.borrow_mut();
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
-            )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+            let mut account_data = RecordData::unpack(raw_data)?;
             if !account_data.is_initialized() {
                 msg!("Record account not initialized");
                 return Err(ProgramError::UninitializedAccount);
             }
             check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
             account_data.authority = *new_authority_info.key;
+            account_data.pending_authority = RecordData::NO_PENDING_AUTHORITY;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::AuthorityChanged {
+                record: *data_info.key,
+                authority: account_data.authority,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::NominateAuthority => {
+            msg!("RecordInstruction::NominateAuthority");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            let new_authority_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
+            let raw_data = &mut data_info.data.borrow_mut();
+            let mut account_data = RecordData::unpack(raw_data)?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            if account_data.version != RecordData::CURRENT_VERSION {
+                msg!(
+                    "Record version {} cannot hold a nomination",
+                    account_data.version
+                );
+                return Err(RecordError::UnsupportedVersion.into());
+            }
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.pending_authority = *new_authority_info.key;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::AuthorityNominated {
+                record: *data_info.key,
+                nominee: account_data.pending_authority,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::AcceptAuthority => {
+            msg!("RecordInstruction::AcceptAuthority");
+            let data_info = next_account_info(account_info_iter)?;
+            let new_authority_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
+            let raw_data = &mut data_info.data.borrow_mut();
+            let mut account_data = RecordData::unpack(raw_data)?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            if account_data.is_immutable() {
+                msg!("Record authority was renounced");
+                return Err(RecordError::Immutable.into());
+            }
+            if !account_data.has_pending_authority() {
+                msg!("No authority nomination pending");
+                return Err(RecordError::NoPendingAuthority.into());
+            }
+            check_authority(new_authority_info, &account_data.pending_authority)?;
+            check_not_paused(&account_data)?;
+            account_data.record_mutation(new_authority_info.key, Clock::get()?.slot)?;
+            account_data.authority = account_data.pending_authority;
+            account_data.pending_authority = RecordData::NO_PENDING_AUTHORITY;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::AuthorityChanged {
+                record: *data_info.key,
+                authority: account_data.authority,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::CancelAuthorityNomination => {
+            msg!("RecordInstruction::CancelAuthorityNomination");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
+            let raw_data = &mut data_info.data.borrow_mut();
+            let mut account_data = RecordData::unpack(raw_data)?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            if !account_data.has_pending_authority() {
+                msg!("No authority nomination pending");
+                return Err(RecordError::NoPendingAuthority.into());
+            }
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.pending_authority = RecordData::NO_PENDING_AUTHORITY;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::NominationCancelled {
+                record: *data_info.key,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::Freeze => {
+            msg!("RecordInstruction::Freeze");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
+            let raw_data = &mut data_info.data.borrow_mut();
+            let mut account_data = RecordData::unpack(raw_data)?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.authority = RecordData::IMMUTABLE_AUTHORITY;
+            account_data.pending_authority = RecordData::NO_PENDING_AUTHORITY;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::AuthorityChanged {
+                record: *data_info.key,
+                authority: account_data.authority,
+            }
+            .emit();
             Ok(())
         }
 
+        RecordInstruction::GrantDelegation {
+            start,
+            end,
+            expiry_slot,
+        } => {
+            msg!("RecordInstruction::GrantDelegation");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            let delegate_info = next_account_info(account_info_iter)?;
+            let delegation_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+
+            check_owner(data_info, program_id)?;
+            let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            check_grants_supported(&account_data)?;
+            if start > end {
+                msg!("Invalid delegated range {}..{}", start, end);
+                return Err(RecordError::InvalidDelegatedRange.into());
+            }
+
+            let (delegation_key, bump) =
+                Delegation::find_address(data_info.key, delegate_info.key, program_id);
+            if delegation_key != *delegation_info.key {
+                msg!("Delegation account does not match the record and delegate");
+                return Err(ProgramError::InvalidSeeds);
+            }
+            if delegation_info.data_is_empty() {
+                create_pda_account(
+                    program_id,
+                    delegation_info,
+                    payer_info,
+                    system_program_info,
+                    Delegation::SIZE,
+                    &[
+                        Delegation::SEED_PREFIX,
+                        data_info.key.as_ref(),
+                        delegate_info.key.as_ref(),
+                        &[bump],
+                    ],
+                )?;
+                account_data.open_grant()?;
+                account_data.pack_into(&mut data_info.data.borrow_mut())?;
+            }
+
+            let delegation =
+                Delegation::new(*data_info.key, *delegate_info.key, start, end, expiry_slot);
+            delegation_info
+                .data
+                .borrow_mut()
+                .get_mut(..Delegation::SIZE)
+                .ok_or(ProgramError::InvalidAccountData)?
+                .copy_from_slice(bytemuck::bytes_of(&delegation));
+            RecordEvent::DelegationGranted {
+                record: *data_info.key,
+                delegate: *delegate_info.key,
+                start,
+                end,
+                expiry_slot,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::RevokeDelegation => {
+            msg!("RecordInstruction::RevokeDelegation");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            let delegation_info = next_account_info(account_info_iter)?;
+            let destination_info = next_account_info(account_info_iter)?;
+
+            check_owner(data_info, program_id)?;
+            let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            check_owner(delegation_info, program_id)?;
+            let delegation = Delegation::unpack(&delegation_info.data.borrow())?;
+            let (delegation_key, _) =
+                Delegation::find_address(data_info.key, &delegation.delegate, program_id);
+            if delegation.record != *data_info.key || delegation_key != *delegation_info.key {
+                msg!("Delegation does not belong to the record");
+                return Err(RecordError::DelegationMismatch.into());
+            }
+            close_grant_account(delegation_info, destination_info)?;
+            account_data.close_grant()?;
+            account_data.pack_into(&mut data_info.data.borrow_mut())?;
+            RecordEvent::DelegationRevoked {
+                record: *data_info.key,
+                delegate: delegation.delegate,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::AddWriter => {
+            msg!("RecordInstruction::AddWriter");
+            process_update_access_list(program_id, accounts, true)
+        }
+
+        RecordInstruction::RemoveWriter => {
+            msg!("RecordInstruction::RemoveWriter");
+            process_update_access_list(program_id, accounts, false)
+        }
+
         RecordInstruction::CloseAccount => {
             msg!("RecordInstruction::CloseAccount");
             let data_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
             let destination_info = next_account_info(account_info_iter)?;
-            let raw_data = &mut data_info.data.borrow_mut();
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
-            )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+            check_owner(data_info, program_id)?;
+            let account_data = RecordData::unpack(&data_info.data.borrow())?;
             if !account_data.is_initialized() {
                 msg!("Record not initialized");
                 return Err(ProgramError::UninitializedAccount);
             }
             check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            if account_data.grants != 0 {
+                msg!(
+                    "Record has {} delegation or access list accounts, close them first",
+                    { account_data.grants }
+                );
+                return Err(RecordError::OpenGrants.into());
+            }
+            if destination_info.key == data_info.key {
+                msg!("Cannot close a record account into itself");
+                return Err(RecordError::CloseIntoSelf.into());
+            }
             let destination_starting_lamports = destination_info.lamports();
             let data_lamports = data_info.lamports();
             **data_info.lamports.borrow_mut() = 0;
             **destination_info.lamports.borrow_mut() = destination_starting_lamports
                 .checked_add(data_lamports)
                 .ok_or(RecordError::Overflow)?;
+
+            data_info.data.borrow_mut().fill(0);
+            data_info.resize(0)?;
+            data_info.assign(&solana_system_interface::program::id());
+            RecordEvent::RecordClosed {
+                record: *data_info.key,
+            }
+            .emit();
             Ok(())
         }
 
@@ -183,47 +970,322 @@ pub fn process_instruction(
             msg!("RecordInstruction::Reallocate");
             let data_info = next_account_info(account_info_iter)?;
             let authority_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let receiver_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
 
-            {
-                let raw_data = &mut data_info.data.borrow_mut();
-                if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                    return Err(ProgramError::InvalidAccountData);
-                }
-                let account_data = bytemuck::try_from_bytes::<RecordData>(
-                    &raw_data[..RecordData::WRITABLE_START_INDEX],
-                )
-                .map_err(|_| ProgramError::InvalidArgument)?;
-                if !account_data.is_initialized() {
-                    msg!("Record not initialized");
-                    return Err(ProgramError::UninitializedAccount);
-                }
-                check_authority(authority_info, &account_data.authority)?;
+            check_owner(data_info, program_id)?;
+            let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_initialized() {
+                msg!("Record not initialized");
+                return Err(ProgramError::UninitializedAccount);
             }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
 
             // needed account length is the sum of the meta data length and the specified
             // data length
-            let needed_account_length = std::mem::size_of::<RecordData>()
-                .checked_add(
-                    usize::try_from(data_length).map_err(|_| ProgramError::InvalidArgument)?,
-                )
-                .unwrap();
+            let needed_account_length = usize::try_from(data_length)
+                .ok()
+                .and_then(|data_length| {
+                    data_length.checked_add(account_data.writable_start_index())
+                })
+                .ok_or(RecordError::Overflow)?;
 
             // reallocate
-            if data_info.data_len() >= needed_account_length {
-                msg!("no additional reallocation needed");
+            if data_info.data_len() == needed_account_length {
+                msg!("no reallocation needed");
                 return Ok(());
             }
-            msg!(
-                "reallocating +{:?} bytes",
-                needed_account_length
-                    .checked_sub(data_info.data_len())
-                    .unwrap(),
-            );
-            data_info.resize(needed_account_length)?;
+            if data_info.data_len() < needed_account_length {
+                resize_rent_exempt(
+                    data_info,
+                    payer_info,
+                    system_program_info,
+                    needed_account_length,
+                )?;
+            } else {
+                msg!(
+                    "reallocating -{:?} bytes",
+                    data_info.data_len() - needed_account_length,
+                );
+                data_info.resize(needed_account_length)?;
+                account_data.length = account_data.length.min(data_length);
+
+                let refund_lamports = data_info
+                    .lamports()
+                    .saturating_sub(Rent::get()?.minimum_balance(needed_account_length));
+                let receiver_starting_lamports = receiver_info.lamports();
+                **data_info.lamports.borrow_mut() -= refund_lamports;
+                **receiver_info.lamports.borrow_mut() = receiver_starting_lamports
+                    .checked_add(refund_lamports)
+                    .ok_or(RecordError::Overflow)?;
+            }
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.pack_into(&mut data_info.data.borrow_mut())?;
+            RecordEvent::RecordResized {
+                record: *data_info.key,
+                data_length,
+            }
+            .emit();
             Ok(())
         }
-        RecordInstruction::ProposeMultiWrite { .. } => initialize_multisig_write(accounts, &instruction.pack()),
+        RecordInstruction::ProposeMultiWrite {
+            offset,
+            data,
+            description,
+        } => {
+            check_owners(program_id, accounts, &[1, 2, 3])?;
+            // the description is stored apart from the write that gets executed
+            let write = RecordInstruction::ProposeMultiWrite {
+                offset,
+                data,
+                description: &[],
+            };
+            initialize_multisig_write(accounts, &write.pack(), description)
+        }
+
+        RecordInstruction::ProposeMultiWriteIfMatch {
+            offset,
+            expected_hash,
+            data,
+            description,
+        } => {
+            check_owners(program_id, accounts, &[1, 2, 3])?;
+            let write = RecordInstruction::ProposeMultiWriteIfMatch {
+                offset,
+                expected_hash,
+                data,
+                description: &[],
+            };
+            initialize_multisig_write(accounts, &write.pack(), description)
+        }
+
+        RecordInstruction::ApproveProposal => {
+            check_owners(program_id, accounts, &[1, 2, 3])?;
+            process_approve_proposal(accounts, handler)
+        }
+
+        RecordInstruction::ProposeNestedApproval => {
+            check_owners(program_id, accounts, &[1, 2, 3, 4])?;
+            initialize_nested_approval(accounts)
+        }
+
+        RecordInstruction::ApproveWithSignatures => {
+            check_owners(program_id, accounts, &[0, 1, 2])?;
+            process_approve_with_signatures(accounts, handler)
+        }
+
+        RecordInstruction::ApproveWithEthSignature {
+            recovery_id,
+            signature,
+        } => {
+            check_owners(program_id, accounts, &[0, 1, 2])?;
+            process_approve_with_eth_signature(accounts, recovery_id, &signature, handler)
+        }
+
+        RecordInstruction::PauseMultisig => {
+            check_owners(program_id, accounts, &[1])?;
+            process_pause(accounts)
+        }
+
+        RecordInstruction::ProposeUnpauseMultisig => {
+            check_owners(program_id, accounts, &[1, 2])?;
+            initialize_unpause_proposal(accounts)
+        }
+
+        RecordInstruction::CloseProposal => {
+            check_owners(program_id, accounts, &[1, 2])?;
+            process_close_proposal(accounts)
+        }
+
+        RecordInstruction::MigrateMultisig => {
+            msg!("RecordInstruction::MigrateMultisig");
+            let multisig_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+            check_owner(multisig_info, program_id)?;
+            process_migrate_multisig(multisig_info, account_info_iter.as_slice(), |new_len| {
+                resize_rent_exempt(multisig_info, payer_info, system_program_info, new_len)
+            })
+        }
+
+        RecordInstruction::MigrateProposal => {
+            msg!("RecordInstruction::MigrateProposal");
+            let member_info = next_account_info(account_info_iter)?;
+            let proposal_info = next_account_info(account_info_iter)?;
+            let multisig_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+            check_owners(program_id, accounts, &[1, 2])?;
+            process_migrate_proposal(member_info, proposal_info, multisig_info, |new_len| {
+                resize_rent_exempt(proposal_info, payer_info, system_program_info, new_len)
+            })
+        }
 
-        RecordInstruction::ApproveProposal => process_approve_proposal(accounts, multisig_handler),
+        RecordInstruction::CreateProposalBuffer { description } => {
+            check_owners(program_id, accounts, &[1, 2, 3])?;
+            initialize_proposal_buffer(accounts, description)
+        }
+
+        RecordInstruction::WriteProposalBuffer { offset, data } => {
+            check_owners(program_id, accounts, &[1])?;
+            process_write_proposal_buffer(accounts, offset, data)
+        }
+
+        RecordInstruction::FinalizeProposalBuffer => {
+            check_owners(program_id, accounts, &[1])?;
+            process_finalize_proposal_buffer(accounts)
+        }
+
+        RecordInstruction::PauseRecord => {
+            msg!("RecordInstruction::PauseRecord");
+            let guardian_info = next_account_info(account_info_iter)?;
+            let data_info = next_account_info(account_info_iter)?;
+            let multisig_info = next_account_info(account_info_iter)?;
+            check_owner(data_info, program_id)?;
+            check_owner(multisig_info, program_id)?;
+            let raw_data = &mut data_info.data.borrow_mut();
+            let mut account_data = RecordData::unpack(raw_data)?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            if account_data.version != RecordData::CURRENT_VERSION {
+                msg!("Record version {} cannot be paused", account_data.version);
+                return Err(RecordError::UnsupportedVersion.into());
+            }
+            if account_data.authority != *multisig_info.key {
+                msg!("Record is not controlled by the multisig");
+                return Err(RecordError::IncorrectAuthority.into());
+            }
+            MultisigConfig::from_account_info(multisig_info)?.check_guardian(guardian_info)?;
+            if account_data.is_paused() {
+                msg!("Record is already paused");
+                return Ok(());
+            }
+            account_data.paused = 1;
+            account_data.record_mutation(guardian_info.key, Clock::get()?.slot)?;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::RecordPaused {
+                record: *data_info.key,
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::ProposeUnpauseRecord { description } => {
+            check_owners(program_id, accounts, &[1, 2, 3])?;
+            let data_info = accounts.get(2).ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_paused() {
+                msg!("Record is not paused");
+                return Err(RecordError::NotPaused.into());
+            }
+            // the description is stored apart from the unpause that gets executed
+            let unpause = RecordInstruction::ProposeUnpauseRecord { description: &[] };
+            initialize_multisig_write(accounts, &unpause.pack(), description)
+        }
+
+        RecordInstruction::ProposeSetGuardian {
+            guardian,
+            description,
+        } => {
+            check_owners(program_id, accounts, &[1, 2])?;
+            initialize_set_guardian_proposal(accounts, &guardian, description)
+        }
+
+        RecordInstruction::Append { data } => {
+            msg!("RecordInstruction::Append");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+
+            check_owner(data_info, program_id)?;
+            let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            if account_data.version != RecordData::CURRENT_VERSION {
+                msg!(
+                    "Record version {} does not track its length",
+                    account_data.version
+                );
+                return Err(RecordError::UnsupportedVersion.into());
+            }
+
+            let start = usize::try_from(account_data.length)
+                .ok()
+                .and_then(|length| length.checked_add(RecordData::WRITABLE_START_INDEX))
+                .ok_or(RecordError::Overflow)?;
+            let end = start.checked_add(data.len()).ok_or(RecordError::Overflow)?;
+
+            if end > data_info.data_len() {
+                resize_rent_exempt(data_info, payer_info, system_program_info, end)?;
+            }
+
+            let raw_data = &mut data_info.data.borrow_mut();
+            raw_data[start..end].copy_from_slice(data);
+            account_data.extend_length(end - RecordData::WRITABLE_START_INDEX);
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::RecordWritten {
+                record: *data_info.key,
+                writer: *authority_info.key,
+                sequence: account_data.sequence,
+                offset: (start - RecordData::WRITABLE_START_INDEX) as u64,
+                len: data.len() as u64,
+                hash: RecordData::hash_contents(data),
+            }
+            .emit();
+            Ok(())
+        }
+
+        RecordInstruction::Migrate => {
+            msg!("RecordInstruction::Migrate");
+            let data_info = next_account_info(account_info_iter)?;
+            let authority_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let system_program_info = next_account_info(account_info_iter)?;
+
+            check_owner(data_info, program_id)?;
+            let mut account_data = RecordData::unpack(&data_info.data.borrow())?;
+            if !account_data.is_initialized() {
+                msg!("Record account not initialized");
+                return Err(ProgramError::UninitializedAccount);
+            }
+            check_authority(authority_info, &account_data.authority)?;
+            check_not_paused(&account_data)?;
+            if account_data.version != RecordDataV1::VERSION {
+                msg!("Record is already at version {}", account_data.version);
+                return Err(RecordError::AlreadyCurrentVersion.into());
+            }
+
+            // the body keeps its length, moving behind the larger header
+            let old_len = data_info.data_len();
+            let new_len = old_len
+                .checked_add(RecordData::WRITABLE_START_INDEX - RecordDataV1::WRITABLE_START_INDEX)
+                .ok_or(RecordError::Overflow)?;
+            resize_rent_exempt(data_info, payer_info, system_program_info, new_len)?;
+
+            let raw_data = &mut data_info.data.borrow_mut();
+            raw_data.copy_within(
+                RecordDataV1::WRITABLE_START_INDEX..old_len,
+                RecordData::WRITABLE_START_INDEX,
+            );
+            account_data.version = RecordData::CURRENT_VERSION;
+            account_data.record_mutation(authority_info.key, Clock::get()?.slot)?;
+            account_data.pack_into(raw_data)?;
+            RecordEvent::RecordMigrated {
+                record: *data_info.key,
+                version: account_data.version,
+            }
+            .emit();
+            Ok(())
+        }
     }
 }
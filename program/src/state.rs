@@ -1,12 +1,16 @@
 //! Program state
 use {
+    crate::error::RecordError,
     bytemuck::{Pod, Zeroable},
+    solana_keccak_hasher::hash,
+    solana_msg::msg,
+    solana_program_error::ProgramError,
     solana_program_pack::IsInitialized,
     solana_pubkey::Pubkey,
 };
 
 /// Header type for recorded account data
-#[repr(C)]
+#[repr(C, packed)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 pub struct RecordData {
     /// Struct version, allows for upgrades to the program
@@ -14,20 +18,413 @@ pub struct RecordData {
 
     /// The account allowed to update the data
     pub authority: Pubkey,
+
+    /// Number of data bytes in use, the position `Append` writes at
+    pub length: u64,
+
+    /// Number of mutations since initialization, increased by every
+    /// instruction changing the record
+    pub sequence: u64,
+
+    /// The account behind the latest mutation: the signing authority, or
+    /// the multisig executing a proposal
+    pub last_writer: Pubkey,
+
+    /// Slot of the latest mutation
+    pub last_update_slot: u64,
+
+    /// The account nominated to take over the authority, until it accepts.
+    /// [`RecordData::NO_PENDING_AUTHORITY`] when there is no nomination.
+    pub pending_authority: Pubkey,
+
+    /// Number of open delegation and access list accounts of the record,
+    /// which must all be closed before the record is
+    pub grants: u64,
+
+    /// Is the record paused by the guardian of its multisig authority:
+    /// 0 - false, 1 - true. Only an unpause proposal of the multisig lifts it.
+    pub paused: u8,
 }
 
-impl RecordData {
-    /// Version to fill in on new created accounts
-    pub const CURRENT_VERSION: u8 = 1;
+/// Header of records created before the length was tracked
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct RecordDataV1 {
+    /// Struct version, always [`RecordDataV1::VERSION`]
+    pub version: u8,
+
+    /// The account allowed to update the data
+    pub authority: Pubkey,
+}
+
+impl RecordDataV1 {
+    /// Version of records with this header
+    pub const VERSION: u8 = 1;
 
     /// Start of writable account data, after version and authority
     pub const WRITABLE_START_INDEX: usize = 33;
 }
 
+impl RecordData {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// Version of accounts not initialized yet
+    pub const UNINITIALIZED_VERSION: u8 = 0;
+
+    /// Header versions the program reads and writes
+    pub const SUPPORTED_VERSIONS: [u8; 2] = [RecordDataV1::VERSION, Self::CURRENT_VERSION];
+
+    /// Start of writable account data, after the header fields:
+    /// 1 + 32 + 8 + 8 + 32 + 8 + 32 + 8 + 1
+    pub const WRITABLE_START_INDEX: usize = 130;
+
+    /// Authority of records frozen by `Freeze`, no one can sign for it
+    pub const IMMUTABLE_AUTHORITY: Pubkey = Pubkey::new_from_array([0; 32]);
+
+    /// Pending authority of records without a nomination
+    pub const NO_PENDING_AUTHORITY: Pubkey = Pubkey::new_from_array([0; 32]);
+
+    /// Seed prefix of record program addresses, keeping them apart from the
+    /// other program addresses of the program
+    pub const SEED_PREFIX: &'static [u8] = b"record";
+
+    /// Program address of a record created from `seeds` by
+    /// `CreateAndInitialize`, which derives it behind
+    /// [`RecordData::SEED_PREFIX`]
+    pub fn find_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        let mut prefixed = vec![Self::SEED_PREFIX];
+        prefixed.extend_from_slice(seeds);
+        Pubkey::find_program_address(&prefixed, program_id)
+    }
+
+    /// Header of a newly initialized record
+    pub fn new(authority: Pubkey, slot: u64) -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            authority,
+            length: 0,
+            sequence: 0,
+            last_writer: authority,
+            last_update_slot: slot,
+            pending_authority: Self::NO_PENDING_AUTHORITY,
+            grants: 0,
+            paused: 0,
+        }
+    }
+
+    /// Reads the header of a record of any supported version. A v1 header
+    /// is widened to the current layout, its whole data counting as in use.
+    /// Unknown versions are rejected rather than read as uninitialized.
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.first() {
+            Some(&RecordDataV1::VERSION) => {
+                let header = data
+                    .get(..RecordDataV1::WRITABLE_START_INDEX)
+                    .and_then(|bytes| bytemuck::try_from_bytes::<RecordDataV1>(bytes).ok())
+                    .ok_or(ProgramError::InvalidAccountData)?;
+                Ok(Self {
+                    version: header.version,
+                    authority: header.authority,
+                    length: (data.len() - RecordDataV1::WRITABLE_START_INDEX) as u64,
+                    sequence: 0,
+                    last_writer: Pubkey::default(),
+                    last_update_slot: 0,
+                    pending_authority: Self::NO_PENDING_AUTHORITY,
+                    grants: 0,
+                    paused: 0,
+                })
+            }
+            Some(&Self::UNINITIALIZED_VERSION) | Some(&Self::CURRENT_VERSION) => data
+                .get(..Self::WRITABLE_START_INDEX)
+                .and_then(|bytes| bytemuck::try_from_bytes::<Self>(bytes).ok())
+                .copied()
+                .ok_or(ProgramError::InvalidAccountData),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    /// Writes the header back in the layout of its version
+    pub fn pack_into(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        if self.version == RecordDataV1::VERSION {
+            let header = RecordDataV1 {
+                version: self.version,
+                authority: self.authority,
+            };
+            data.get_mut(..RecordDataV1::WRITABLE_START_INDEX)
+                .ok_or(ProgramError::InvalidAccountData)?
+                .copy_from_slice(bytemuck::bytes_of(&header));
+        } else {
+            data.get_mut(..Self::WRITABLE_START_INDEX)
+                .ok_or(ProgramError::InvalidAccountData)?
+                .copy_from_slice(bytemuck::bytes_of(self));
+        }
+        Ok(())
+    }
+
+    /// Extends the data in use to cover a write ending at `end`, relative to
+    /// the start of writable account data
+    pub fn extend_length(&mut self, end: usize) {
+        self.length = self.length.max(end as u64);
+    }
+
+    /// Records a mutation by `writer` at `slot`. Not persisted for v1 headers,
+    /// which have no room for it.
+    pub fn record_mutation(&mut self, writer: &Pubkey, slot: u64) -> Result<(), ProgramError> {
+        self.sequence = self.sequence.checked_add(1).ok_or(RecordError::Overflow)?;
+        self.last_writer = *writer;
+        self.last_update_slot = slot;
+        Ok(())
+    }
+
+    /// Counts a newly created delegation or access list account
+    pub fn open_grant(&mut self) -> Result<(), ProgramError> {
+        self.grants = self.grants.checked_add(1).ok_or(RecordError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a closed delegation or access list account
+    pub fn close_grant(&mut self) -> Result<(), ProgramError> {
+        self.grants = self.grants.checked_sub(1).ok_or(RecordError::Overflow)?;
+        Ok(())
+    }
+
+    /// Was the authority of this record renounced
+    pub fn is_immutable(&self) -> bool {
+        self.authority == Self::IMMUTABLE_AUTHORITY
+    }
+
+    /// Is the record paused by the guardian of its multisig authority
+    pub fn is_paused(&self) -> bool {
+        self.paused != 0
+    }
+
+    /// Is an authority nominated to take over this record
+    pub fn has_pending_authority(&self) -> bool {
+        self.pending_authority != Self::NO_PENDING_AUTHORITY
+    }
+
+    /// Hash of a range of record data, as expected by conditional writes
+    pub fn hash_contents(contents: &[u8]) -> [u8; 32] {
+        hash(contents).0
+    }
+
+    /// Start of writable account data for the version of this header
+    pub fn writable_start_index(&self) -> usize {
+        if self.version == RecordDataV1::VERSION {
+            RecordDataV1::WRITABLE_START_INDEX
+        } else {
+            Self::WRITABLE_START_INDEX
+        }
+    }
+}
+
 impl IsInitialized for RecordData {
     /// Is initialized
     fn is_initialized(&self) -> bool {
-        self.version == Self::CURRENT_VERSION
+        Self::SUPPORTED_VERSIONS.contains(&self.version)
+    }
+}
+
+/// Type of the program accounts kept next to records, stored in their first
+/// byte. Records hold their version there instead, so types are picked well
+/// above any record version and one kind of account never reads as another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AccountType {
+    /// A [`Delegation`]
+    Delegation = 0x81,
+    /// An [`AccessList`]
+    AccessList = 0x82,
+}
+
+/// Write access to a byte range of a record, granted by its authority to a
+/// delegate. Stored at the program address derived by
+/// [`Delegation::find_address`].
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Delegation {
+    /// Account type, always [`AccountType::Delegation`]
+    pub account_type: u8,
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The record the delegate may write to
+    pub record: Pubkey,
+
+    /// The account allowed to write the range
+    pub delegate: Pubkey,
+
+    /// Start of the writable range, relative to the start of record data
+    pub start: u64,
+
+    /// End of the writable range, exclusive
+    pub end: u64,
+
+    /// Last slot the delegation is valid in, [`Delegation::NO_EXPIRY`] for
+    /// delegations valid until revoked
+    pub expiry_slot: u64,
+}
+
+impl Delegation {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Size of the delegation account data: 1 + 1 + 32 + 32 + 8 + 8 + 8
+    pub const SIZE: usize = 90;
+
+    /// Seed prefix of delegation program addresses
+    pub const SEED_PREFIX: &'static [u8] = b"delegation";
+
+    /// Expiry slot of delegations valid until revoked
+    pub const NO_EXPIRY: u64 = 0;
+
+    /// Delegation of `start..end` of `record` to `delegate`
+    pub fn new(record: Pubkey, delegate: Pubkey, start: u64, end: u64, expiry_slot: u64) -> Self {
+        Self {
+            account_type: AccountType::Delegation as u8,
+            version: Self::CURRENT_VERSION,
+            record,
+            delegate,
+            start,
+            end,
+            expiry_slot,
+        }
+    }
+
+    /// Program address holding the delegation of `record` to `delegate`
+    pub fn find_address(record: &Pubkey, delegate: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, record.as_ref(), delegate.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Reads a delegation from its account data, rejecting accounts of any
+    /// other type
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        data.get(..Self::SIZE)
+            .and_then(|bytes| bytemuck::try_from_bytes::<Self>(bytes).ok())
+            .copied()
+            .filter(|delegation| {
+                delegation.account_type == AccountType::Delegation as u8
+                    && delegation.version == Self::CURRENT_VERSION
+            })
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    /// Has the delegation expired at `slot`
+    pub fn is_expired(&self, slot: u64) -> bool {
+        self.expiry_slot != Self::NO_EXPIRY && slot > self.expiry_slot
+    }
+
+    /// Does the delegated range cover `start..end` of the record data
+    pub fn covers(&self, start: u64, end: u64) -> bool {
+        self.start <= start && end <= self.end
+    }
+}
+
+/// Maximum number of writers in an access list
+pub const MAX_WRITERS: usize = 8;
+
+/// Keys allowed to write anywhere in a record besides its authority, managed
+/// by the authority. Stored at the program address derived by
+/// [`AccessList::find_address`].
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct AccessList {
+    /// Account type, always [`AccountType::AccessList`]
+    pub account_type: u8,
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The record the writers may write to
+    pub record: Pubkey,
+
+    /// Listed writers, free slots hold the default pubkey
+    pub writers: [Pubkey; MAX_WRITERS],
+}
+
+impl AccessList {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Size of the access list account data: 1 + 1 + 32 + 32 * MAX_WRITERS
+    pub const SIZE: usize = 1 + 1 + 32 + 32 * MAX_WRITERS;
+
+    /// Seed prefix of access list program addresses
+    pub const SEED_PREFIX: &'static [u8] = b"access-list";
+
+    /// Empty access list of `record`
+    pub fn new(record: Pubkey) -> Self {
+        Self {
+            account_type: AccountType::AccessList as u8,
+            version: Self::CURRENT_VERSION,
+            record,
+            writers: [Pubkey::default(); MAX_WRITERS],
+        }
+    }
+
+    /// Program address holding the access list of `record`
+    pub fn find_address(record: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, record.as_ref()], program_id)
+    }
+
+    /// Reads an access list from its account data, rejecting accounts of any
+    /// other type
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        bytemuck::try_from_bytes::<Self>(data)
+            .ok()
+            .copied()
+            .filter(|access_list| {
+                access_list.account_type == AccountType::AccessList as u8
+                    && access_list.version == Self::CURRENT_VERSION
+            })
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    /// Listed writers
+    pub fn writers(&self) -> impl Iterator<Item = &Pubkey> {
+        self.writers.iter().filter(|key| **key != Pubkey::default())
+    }
+
+    /// Is the key a listed writer
+    pub fn is_writer(&self, writer: &Pubkey) -> bool {
+        self.writers().any(|key| key == writer)
+    }
+
+    /// Lists a writer in a free slot
+    pub fn add_writer(&mut self, writer: &Pubkey) -> Result<(), ProgramError> {
+        if self.is_writer(writer) {
+            msg!("Writer is already listed");
+            return Err(RecordError::WriterAlreadyListed.into());
+        }
+        let slot = self
+            .writers
+            .iter_mut()
+            .find(|key| **key == Pubkey::default())
+            .ok_or_else(|| {
+                msg!("Too many writers, remove some first");
+                RecordError::AccessListFull
+            })?;
+        *slot = *writer;
+        Ok(())
+    }
+
+    /// Frees the slot of a listed writer
+    pub fn remove_writer(&mut self, writer: &Pubkey) -> Result<(), ProgramError> {
+        let slot = self
+            .writers
+            .iter_mut()
+            .find(|key| *key == writer)
+            .ok_or_else(|| {
+                msg!("Writer is not listed");
+                RecordError::WriterNotListed
+            })?;
+        *slot = Pubkey::default();
+        Ok(())
     }
 }
 
@@ -36,21 +433,47 @@ pub(crate) mod tests {
     use {super::*, solana_program_error::ProgramError};
 
     /// Version for tests
-    pub const TEST_VERSION: u8 = 1;
+    pub const TEST_VERSION: u8 = 2;
     /// Pubkey for tests
     pub const TEST_PUBKEY: Pubkey = Pubkey::new_from_array([100; 32]);
+    /// Length for tests
+    pub const TEST_LENGTH: u64 = 8;
+    /// Sequence for tests
+    pub const TEST_SEQUENCE: u64 = 3;
+    /// Slot for tests
+    pub const TEST_SLOT: u64 = 1234;
+    /// Pending authority for tests
+    pub const TEST_PENDING_AUTHORITY: Pubkey = Pubkey::new_from_array([101; 32]);
+    /// Grants for tests
+    pub const TEST_GRANTS: u64 = 2;
+    /// Pause flag for tests
+    pub const TEST_PAUSED: u8 = 1;
     /// Bytes for tests
     pub const TEST_BYTES: [u8; 8] = [42; 8];
     /// `RecordData` for tests
     pub const TEST_RECORD_DATA: RecordData = RecordData {
         version: TEST_VERSION,
         authority: TEST_PUBKEY,
+        length: TEST_LENGTH,
+        sequence: TEST_SEQUENCE,
+        last_writer: TEST_PUBKEY,
+        last_update_slot: TEST_SLOT,
+        pending_authority: TEST_PENDING_AUTHORITY,
+        grants: TEST_GRANTS,
+        paused: TEST_PAUSED,
     };
 
     #[test]
     fn serialize_data() {
         let mut expected = vec![TEST_VERSION];
         expected.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        expected.extend_from_slice(&TEST_LENGTH.to_le_bytes());
+        expected.extend_from_slice(&TEST_SEQUENCE.to_le_bytes());
+        expected.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        expected.extend_from_slice(&TEST_SLOT.to_le_bytes());
+        expected.extend_from_slice(&TEST_PENDING_AUTHORITY.to_bytes());
+        expected.extend_from_slice(&TEST_GRANTS.to_le_bytes());
+        expected.push(TEST_PAUSED);
         assert_eq!(bytemuck::bytes_of(&TEST_RECORD_DATA), expected);
         assert_eq!(
             *bytemuck::try_from_bytes::<RecordData>(&expected).unwrap(),
@@ -58,10 +481,172 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn unpack_v1_data() {
+        let mut data = vec![RecordDataV1::VERSION];
+        data.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        data.extend_from_slice(&TEST_BYTES);
+
+        let header = RecordData::unpack(&data).unwrap();
+        assert!(header.is_initialized());
+        assert_eq!(header.authority, TEST_PUBKEY);
+        assert_eq!({ header.length }, TEST_BYTES.len() as u64);
+        assert_eq!(
+            header.writable_start_index(),
+            RecordDataV1::WRITABLE_START_INDEX
+        );
+
+        let mut packed = vec![0; data.len()];
+        header.pack_into(&mut packed).unwrap();
+        assert_eq!(
+            packed[..RecordDataV1::WRITABLE_START_INDEX],
+            data[..RecordDataV1::WRITABLE_START_INDEX]
+        );
+        assert_eq!(packed[RecordDataV1::WRITABLE_START_INDEX..], [0; 8]);
+    }
+
+    #[test]
+    fn unpack_current_data() {
+        let mut data = bytemuck::bytes_of(&TEST_RECORD_DATA).to_vec();
+        data.extend_from_slice(&TEST_BYTES);
+        assert_eq!(RecordData::unpack(&data).unwrap(), TEST_RECORD_DATA);
+
+        let err = RecordData::unpack(&data[..RecordData::WRITABLE_START_INDEX - 1]).unwrap_err();
+        assert_eq!(err, ProgramError::InvalidAccountData);
+    }
+
+    #[test]
+    fn unpack_unsupported_version() {
+        let mut data = bytemuck::bytes_of(&TEST_RECORD_DATA).to_vec();
+        data[0] = RecordData::CURRENT_VERSION + 1;
+        let err = RecordData::unpack(&data).unwrap_err();
+        assert_eq!(err, ProgramError::InvalidAccountData);
+    }
+
+    #[test]
+    fn record_mutation() {
+        let writer = Pubkey::new_from_array([7; 32]);
+        let mut header = TEST_RECORD_DATA;
+        header.record_mutation(&writer, TEST_SLOT + 1).unwrap();
+        assert_eq!({ header.sequence }, TEST_SEQUENCE + 1);
+        assert_eq!(header.last_writer, writer);
+        assert_eq!({ header.last_update_slot }, TEST_SLOT + 1);
+
+        header.sequence = u64::MAX;
+        assert_eq!(
+            header.record_mutation(&writer, TEST_SLOT).unwrap_err(),
+            RecordError::Overflow.into()
+        );
+    }
+
+    #[test]
+    fn delegation() {
+        let delegation = Delegation::new(TEST_PUBKEY, TEST_PENDING_AUTHORITY, 8, 16, TEST_SLOT);
+        let data = bytemuck::bytes_of(&delegation);
+        assert_eq!(data.len(), Delegation::SIZE);
+        assert_eq!(Delegation::unpack(data).unwrap(), delegation);
+        assert_eq!(
+            Delegation::unpack(&[0; Delegation::SIZE]).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        assert!(delegation.covers(8, 16));
+        assert!(delegation.covers(10, 12));
+        assert!(!delegation.covers(7, 12));
+        assert!(!delegation.covers(12, 17));
+
+        assert!(!delegation.is_expired(TEST_SLOT));
+        assert!(delegation.is_expired(TEST_SLOT + 1));
+        let unlimited = Delegation {
+            expiry_slot: Delegation::NO_EXPIRY,
+            ..delegation
+        };
+        assert!(!unlimited.is_expired(u64::MAX));
+    }
+
+    #[test]
+    fn unpack_record_as_delegation() {
+        // a v1 record holding the record key where a delegation does
+        let mut data = vec![RecordDataV1::VERSION];
+        data.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        data.extend_from_slice(&TEST_PENDING_AUTHORITY.to_bytes());
+        data.resize(Delegation::SIZE, 0xff);
+        assert_eq!(
+            Delegation::unpack(&data).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        let mut data = bytemuck::bytes_of(&TEST_RECORD_DATA).to_vec();
+        data.resize(Delegation::SIZE.max(data.len()), 0);
+        assert_eq!(
+            Delegation::unpack(&data).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn access_list() {
+        let writer = Pubkey::new_from_array([200; 32]);
+        let mut access_list = AccessList::new(TEST_PUBKEY);
+        assert_eq!(bytemuck::bytes_of(&access_list).len(), AccessList::SIZE);
+
+        access_list.add_writer(&writer).unwrap();
+        assert!(access_list.is_writer(&writer));
+        assert_eq!(
+            access_list.add_writer(&writer).unwrap_err(),
+            RecordError::WriterAlreadyListed.into()
+        );
+        for index in 1..MAX_WRITERS {
+            access_list
+                .add_writer(&Pubkey::new_from_array([index as u8; 32]))
+                .unwrap();
+        }
+        assert_eq!(
+            access_list.add_writer(&TEST_PUBKEY).unwrap_err(),
+            RecordError::AccessListFull.into()
+        );
+
+        access_list.remove_writer(&writer).unwrap();
+        assert!(!access_list.is_writer(&writer));
+        assert_eq!(access_list.writers().count(), MAX_WRITERS - 1);
+        assert_eq!(
+            access_list.remove_writer(&writer).unwrap_err(),
+            RecordError::WriterNotListed.into()
+        );
+
+        let data = bytemuck::bytes_of(&access_list);
+        assert_eq!(AccessList::unpack(data).unwrap(), access_list);
+        assert_eq!(
+            AccessList::unpack(&data[..AccessList::SIZE - 1]).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        // a delegation or record of the same size is not an access list
+        let mut data = bytemuck::bytes_of(&Delegation::new(TEST_PUBKEY, writer, 0, 8, 0)).to_vec();
+        data.resize(AccessList::SIZE, 0);
+        assert_eq!(
+            AccessList::unpack(&data).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        let mut data = bytemuck::bytes_of(&TEST_RECORD_DATA).to_vec();
+        data.resize(AccessList::SIZE, 0);
+        assert_eq!(
+            AccessList::unpack(&data).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
     #[test]
     fn deserialize_invalid_slice() {
         let mut expected = vec![TEST_VERSION];
         expected.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        expected.extend_from_slice(&TEST_LENGTH.to_le_bytes());
+        expected.extend_from_slice(&TEST_SEQUENCE.to_le_bytes());
+        expected.extend_from_slice(&TEST_PUBKEY.to_bytes());
+        expected.extend_from_slice(&TEST_SLOT.to_le_bytes());
+        expected.extend_from_slice(&TEST_PENDING_AUTHORITY.to_bytes());
+        expected.extend_from_slice(&TEST_GRANTS.to_le_bytes());
+        expected.push(TEST_PAUSED);
         expected.extend_from_slice(&TEST_BYTES);
         let err = bytemuck::try_from_bytes::<RecordData>(&expected)
             .map_err(|_| ProgramError::InvalidArgument)
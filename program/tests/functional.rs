@@ -2,13 +2,18 @@
 
 use {
     all2all_controller::{
-        error::RecordError, id, instruction, processor::process_instruction, state::RecordData,
+        error::RecordError,
+        id, instruction,
+        processor::process_instruction,
+        state::{AccessList, Delegation, RecordData, RecordDataV1},
     },
     solana_instruction::{error::InstructionError, AccountMeta, Instruction},
     solana_program_test::*,
     solana_pubkey::Pubkey,
     solana_rent::Rent,
     solana_sdk::{
+        account::Account,
+        clock::Clock,
         signature::{Keypair, Signer},
         transaction::{Transaction, TransactionError},
     },
@@ -150,6 +155,285 @@ async fn initialize_twice_fail() {
     );
 }
 
+#[tokio::test]
+async fn initialize_fail_wrong_owner() {
+    let context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let account_length = RecordData::WRITABLE_START_INDEX;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &account.pubkey(),
+                Rent::default().minimum_balance(account_length),
+                account_length as u64,
+                &Pubkey::new_unique(),
+            ),
+            instruction::initialize(&account.pubkey(), &authority.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &account],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, InstructionError::IllegalOwner)
+    );
+}
+
+#[tokio::test]
+async fn create_and_initialize_success() {
+    let context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data_length = 8u64;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_and_initialize(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            data_length,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &account],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_length = RecordData::WRITABLE_START_INDEX + data_length as usize;
+    assert_eq!(account.owner, id());
+    assert_eq!(account.data.len(), account_length);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(account_length)
+    );
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+    assert_eq!(account_data.version, RecordData::CURRENT_VERSION);
+    assert_eq!({ account_data.length }, 0);
+}
+
+#[tokio::test]
+async fn create_and_initialize_with_seeds_success() {
+    let context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let (account, bump) = RecordData::find_address(&[authority.pubkey().as_ref()], &id());
+    let data_length = 8u64;
+    let create = |seeds: &[&[u8]]| {
+        Transaction::new_signed_with_payer(
+            &[instruction::create_and_initialize_with_seeds(
+                &account,
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                data_length,
+                seeds,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        )
+    };
+
+    // seeds deriving another address do not sign for the record
+    let (_, other_bump) = RecordData::find_address(&[b"other"], &id());
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(create(&[b"other", &[other_bump]]))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::PrivilegeEscalation)
+    );
+
+    // nor do the seeds of the record address without the prefix
+    let (_, unprefixed_bump) = Pubkey::find_program_address(&[authority.pubkey().as_ref()], &id());
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(create(&[authority.pubkey().as_ref(), &[unprefixed_bump]]))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::PrivilegeEscalation)
+    );
+
+    context
+        .banks_client
+        .process_transaction(create(&[authority.pubkey().as_ref(), &[bump]]))
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, id());
+    assert_eq!(
+        account.data.len(),
+        RecordData::WRITABLE_START_INDEX + data_length as usize
+    );
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn create_and_initialize_with_seeds_prefunded_success() {
+    let context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let (account, bump) = RecordData::find_address(&[authority.pubkey().as_ref()], &id());
+    let data_length = 8u64;
+
+    // funding the record address first does not block its creation
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &account,
+                Rent::default().minimum_balance(0),
+            ),
+            instruction::create_and_initialize_with_seeds(
+                &account,
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                data_length,
+                &[authority.pubkey().as_ref(), &[bump]],
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap();
+    let account_length = RecordData::WRITABLE_START_INDEX + data_length as usize;
+    assert_eq!(account.owner, id());
+    assert_eq!(account.data.len(), account_length);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(account_length)
+    );
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn create_and_initialize_fail_reserved_seeds() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    initialize_storage_account(&mut context, &authority, &account, &[222u8; 8]).await;
+
+    // seeds of the access list of an existing record cannot create a record
+    let (access_list, bump) = AccessList::find_address(&account.pubkey(), &id());
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_and_initialize_with_seeds(
+            &access_list,
+            &context.payer.pubkey(),
+            &context.payer.pubkey(),
+            AccessList::SIZE as u64,
+            &[AccessList::SEED_PREFIX, account.pubkey().as_ref(), &[bump]],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn custom_program_id_success() {
+    // the same program deployed at another address, e.g. on a local validator
+    let program_id = Pubkey::new_unique();
+    let context = ProgramTest::new(
+        "all2all_controller",
+        program_id,
+        processor!(process_instruction),
+    )
+    .start_with_context()
+    .await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[111u8; 8];
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::create_and_initialize_with_program_id(
+                &program_id,
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                data.len() as u64,
+            ),
+            instruction::write_with_program_id(
+                &program_id,
+                &account.pubkey(),
+                &authority.pubkey(),
+                0,
+                data,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &account, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(&account.data[RecordData::WRITABLE_START_INDEX..], data);
+}
+
 #[tokio::test]
 async fn write_success() {
     let mut context = program_test().start_with_context().await;
@@ -191,6 +475,49 @@ async fn write_success() {
     assert_eq!(&account.data[RecordData::WRITABLE_START_INDEX..], new_data);
 }
 
+#[tokio::test]
+async fn write_tracks_sequence_and_last_writer() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let new_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_authority(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            instruction::write(&account.pubkey(), &new_authority.pubkey(), 0, &[200u8; 4]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    // initial write, authority change and second write
+    assert_eq!({ account_data.sequence }, 3);
+    assert_eq!({ account_data.last_writer }, new_authority.pubkey());
+    assert_eq!({ account_data.last_update_slot }, clock.slot);
+}
+
 #[tokio::test]
 async fn write_fail_wrong_authority() {
     let mut context = program_test().start_with_context().await;
@@ -262,6 +589,50 @@ async fn write_fail_unsigned() {
     );
 }
 
+#[tokio::test]
+async fn write_fail_foreign_owner() {
+    let authority = Keypair::new();
+    let account = Pubkey::new_unique();
+
+    // a well-formed record owned by another program
+    let mut data = vec![0u8; RecordData::WRITABLE_START_INDEX + 8];
+    RecordData::new(authority.pubkey(), 0)
+        .pack_into(&mut data)
+        .unwrap();
+    let mut program_test = program_test();
+    program_test.add_account(
+        account,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: Pubkey::new_unique(),
+            ..Account::default()
+        },
+    );
+    let context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write(
+            &account,
+            &authority.pubkey(),
+            0,
+            &[1u8; 8],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+    );
+}
+
 #[tokio::test]
 async fn close_account_success() {
     let mut context = program_test().start_with_context().await;
@@ -304,7 +675,7 @@ async fn close_account_success() {
 }
 
 #[tokio::test]
-async fn close_account_fail_wrong_authority() {
+async fn close_account_fail_into_itself() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -312,12 +683,111 @@ async fn close_account_fail_wrong_authority() {
     let data = &[222u8; 8];
     initialize_storage_account(&mut context, &authority, &account, data).await;
 
-    let wrong_authority = Keypair::new();
     let transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id: id(),
-            accounts: vec![
-                AccountMeta::new(account.pubkey(), false),
+        &[instruction::close_account(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &account.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::CloseIntoSelf as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn close_account_revival_fail() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let recipient = Pubkey::new_unique();
+    let revival_lamports = Rent::default().minimum_balance(0);
+
+    // refunding the closed account in the same transaction does not bring
+    // back the record, neither for its old authority nor for a new one
+    for revival in [
+        instruction::write(&account.pubkey(), &authority.pubkey(), 0, &[200u8; 8]),
+        instruction::initialize(&account.pubkey(), &authority.pubkey()),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                instruction::close_account(&account.pubkey(), &authority.pubkey(), &recipient),
+                system_instruction::transfer(
+                    &context.payer.pubkey(),
+                    &account.pubkey(),
+                    revival_lamports,
+                ),
+                revival,
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(2, InstructionError::IllegalOwner)
+        );
+    }
+
+    // once closed, the account is gone
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &recipient,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn close_account_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction {
+            program_id: id(),
+            accounts: vec![
+                AccountMeta::new(account.pubkey(), false),
                 AccountMeta::new_readonly(wrong_authority.pubkey(), true),
                 AccountMeta::new(Pubkey::new_unique(), false),
             ],
@@ -401,28 +871,1397 @@ async fn set_authority_success() {
         .await
         .unwrap();
 
-    let account_handle = context
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = bytemuck::try_from_bytes::<RecordData>(
+        &account_handle.data[..RecordData::WRITABLE_START_INDEX],
+    )
+    .unwrap();
+    assert_eq!(account_data.authority, new_authority.pubkey());
+
+    let new_data = &[200u8; 8];
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write(
+            &account.pubkey(),
+            &new_authority.pubkey(),
+            0,
+            new_data,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = bytemuck::try_from_bytes::<RecordData>(
+        &account_handle.data[..RecordData::WRITABLE_START_INDEX],
+    )
+    .unwrap();
+    assert_eq!(account_data.authority, new_authority.pubkey());
+    assert_eq!(account_data.version, RecordData::CURRENT_VERSION);
+    assert_eq!(
+        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
+        new_data,
+    );
+}
+
+#[tokio::test]
+async fn set_authority_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction {
+            program_id: id(),
+            accounts: vec![
+                AccountMeta::new(account.pubkey(), false),
+                AccountMeta::new_readonly(wrong_authority.pubkey(), true),
+                AccountMeta::new(Pubkey::new_unique(), false),
+            ],
+            data: instruction::RecordInstruction::SetAuthority.pack(),
+        }],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_authority_fail_unsigned() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction {
+            program_id: id(),
+            accounts: vec![
+                AccountMeta::new(account.pubkey(), false),
+                AccountMeta::new_readonly(authority.pubkey(), false),
+                AccountMeta::new(Pubkey::new_unique(), false),
+            ],
+            data: instruction::RecordInstruction::SetAuthority.pack(),
+        }],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn nominate_and_accept_authority_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let new_authority = Keypair::new();
+
+    // the nomination alone leaves the current authority in charge
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::nominate_authority(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            instruction::write(&account.pubkey(), &authority.pubkey(), 0, &[200u8; 8]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = RecordData::unpack(&account_handle.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+    assert_eq!(account_data.pending_authority, new_authority.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_authority(
+            &account.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = RecordData::unpack(&account_handle.data).unwrap();
+    assert_eq!(account_data.authority, new_authority.pubkey());
+    assert!(!account_data.has_pending_authority());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write(
+            &account.pubkey(),
+            &authority.pubkey(),
+            0,
+            &[111u8; 8],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn accept_authority_fail_not_nominated() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let new_authority = Keypair::new();
+    let wrong_authority = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::nominate_authority(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            instruction::accept_authority(&account.pubkey(), &wrong_authority.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &wrong_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn cancel_authority_nomination_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let new_authority = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::nominate_authority(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            instruction::cancel_authority_nomination(&account.pubkey(), &authority.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // the former nominee cannot accept anymore
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_authority(
+            &account.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::NoPendingAuthority as u32)
+        )
+    );
+
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+    assert!(!account_data.has_pending_authority());
+}
+
+#[tokio::test]
+async fn delegate_write_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let delegate = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::grant_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                2,
+                6,
+                Delegation::NO_EXPIRY,
+            ),
+            instruction::write_as_delegate(&account.pubkey(), &delegate.pubkey(), 2, &[1u8; 4]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &delegate],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
+        &[222, 222, 1, 1, 1, 1, 222, 222]
+    );
+    let account_data = RecordData::unpack(&account_handle.data).unwrap();
+    assert_eq!(account_data.authority, authority.pubkey());
+    assert_eq!({ account_data.last_writer }, delegate.pubkey());
+
+    // writes past the delegated range are rejected
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write_as_delegate(
+            &account.pubkey(),
+            &delegate.pubkey(),
+            4,
+            &[2u8; 4],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &delegate],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::OutsideDelegatedRange as u32)
+        )
+    );
+
+    // the delegate cannot act as the authority
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write(
+            &account.pubkey(),
+            &delegate.pubkey(),
+            2,
+            &[2u8; 4],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &delegate],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn grant_delegation_prefunded_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let delegate = Keypair::new();
+    let (delegation, _) = Delegation::find_address(&account.pubkey(), &delegate.pubkey(), &id());
+
+    // funding the delegation address first does not block the grant
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &delegation,
+                Rent::default().minimum_balance(0),
+            ),
+            instruction::grant_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                8,
+                Delegation::NO_EXPIRY,
+            ),
+            instruction::write_as_delegate(&account.pubkey(), &delegate.pubkey(), 0, &[1u8; 8]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &delegate],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let delegation_account = context
+        .banks_client
+        .get_account(delegation)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(delegation_account.owner, id());
+    assert_eq!(
+        delegation_account.lamports,
+        Rent::default().minimum_balance(Delegation::SIZE)
+    );
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
+        &[1u8; 8]
+    );
+    let account_data = RecordData::unpack(&account_handle.data).unwrap();
+    assert_eq!({ account_data.grants }, 1);
+}
+
+#[tokio::test]
+async fn delegate_write_fail_expired() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let delegate = Keypair::new();
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::grant_delegation(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &delegate.pubkey(),
+            &context.payer.pubkey(),
+            0,
+            8,
+            clock.slot + 1,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    context.warp_to_slot(clock.slot + 10).unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write_as_delegate(
+            &account.pubkey(),
+            &delegate.pubkey(),
+            0,
+            &[1u8; 8],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &delegate],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::DelegationExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn delegate_write_fail_record_as_delegation() {
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let attacker = Keypair::new();
+
+    // a v1 record laid out like a delegation of the whole record to the
+    // attacker: version 1, then the record key, the delegate and the range
+    let forged = Pubkey::new_unique();
+    let mut data = vec![RecordDataV1::VERSION];
+    data.extend_from_slice(account.pubkey().as_ref());
+    data.extend_from_slice(attacker.pubkey().as_ref());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.extend_from_slice(&u64::MAX.to_le_bytes());
+    data.extend_from_slice(&Delegation::NO_EXPIRY.to_le_bytes());
+    let mut program_test = program_test();
+    program_test.add_account(
+        forged,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: id(),
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    initialize_storage_account(&mut context, &authority, &account, &[222u8; 8]).await;
+
+    let mut write =
+        instruction::write_as_delegate(&account.pubkey(), &attacker.pubkey(), 0, &[1u8; 8]);
+    write.accounts[2] = AccountMeta::new_readonly(forged, false);
+    let transaction = Transaction::new_signed_with_payer(
+        &[write],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn revoke_delegation_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let delegate = Keypair::new();
+    let (delegation, _) = Delegation::find_address(&account.pubkey(), &delegate.pubkey(), &id());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::grant_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                8,
+                Delegation::NO_EXPIRY,
+            ),
+            instruction::revoke_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(delegation)
+        .await
+        .unwrap()
+        .is_none());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::write_as_delegate(
+            &account.pubkey(),
+            &delegate.pubkey(),
+            0,
+            &[1u8; 8],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &delegate],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IllegalOwner)
+    );
+}
+
+#[tokio::test]
+async fn listed_writers_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let writer1 = Keypair::new();
+    let writer2 = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::add_writer(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &writer1.pubkey(),
+                &context.payer.pubkey(),
+            ),
+            instruction::add_writer(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &writer2.pubkey(),
+                &context.payer.pubkey(),
+            ),
+            instruction::write_as_listed_writer(&account.pubkey(), &writer1.pubkey(), 0, &[1u8; 4]),
+            instruction::write_as_listed_writer(&account.pubkey(), &writer2.pubkey(), 4, &[2u8; 4]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &writer1, &writer2],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
+        &[1, 1, 1, 1, 2, 2, 2, 2]
+    );
+
+    // a removed writer cannot write anymore
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::remove_writer(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &writer1.pubkey(),
+                &context.payer.pubkey(),
+            ),
+            instruction::write_as_listed_writer(&account.pubkey(), &writer1.pubkey(), 0, &[3u8; 4]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &writer1],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(RecordError::NotAListedWriter as u32)
+        )
+    );
+
+    // listed writers do not share the authority
+    for instruction in [
+        instruction::set_authority(&account.pubkey(), &writer2.pubkey(), &writer2.pubkey()),
+        instruction::close_account(&account.pubkey(), &writer2.pubkey(), &writer2.pubkey()),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &writer2],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn listed_writer_fail_forged_access_list() {
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let attacker = Keypair::new();
+
+    // a well-formed access list listing the attacker, at an address other
+    // than the access list of the record
+    let forged = Pubkey::new_unique();
+    let mut access_list = AccessList::new(account.pubkey());
+    access_list.add_writer(&attacker.pubkey()).unwrap();
+    let data = bytemuck::bytes_of(&access_list).to_vec();
+    let mut program_test = program_test();
+    program_test.add_account(
+        forged,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: id(),
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+    initialize_storage_account(&mut context, &authority, &account, &[222u8; 8]).await;
+
+    let mut write =
+        instruction::write_as_listed_writer(&account.pubkey(), &attacker.pubkey(), 0, &[1u8; 8]);
+    write.accounts[2] = AccountMeta::new_readonly(forged, false);
+    let transaction = Transaction::new_signed_with_payer(
+        &[write],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &attacker],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
+
+#[tokio::test]
+async fn remove_last_writer_closes_access_list() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let writer = Keypair::new();
+    let receiver = Pubkey::new_unique();
+    let (access_list, _) = AccessList::find_address(&account.pubkey(), &id());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::add_writer(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &writer.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let access_list_lamports = context
+        .banks_client
+        .get_account(access_list)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::remove_writer(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &writer.pubkey(),
+            &receiver,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(access_list)
+        .await
+        .unwrap()
+        .is_none());
+    let receiver_account = context
+        .banks_client
+        .get_account(receiver)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receiver_account.lamports, access_list_lamports);
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = bytemuck::try_from_bytes::<RecordData>(
+        &account_handle.data[..RecordData::WRITABLE_START_INDEX],
+    )
+    .unwrap();
+    assert_eq!({ account_data.grants }, 0);
+}
+
+#[tokio::test]
+async fn add_writer_prefunded_access_list_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let writer = Keypair::new();
+    let (access_list, _) = AccessList::find_address(&account.pubkey(), &id());
+
+    // anyone may fund the access list address before it is created
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            &access_list,
+            Rent::default().minimum_balance(0),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::add_writer(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &writer.pubkey(),
+                &context.payer.pubkey(),
+            ),
+            instruction::write_as_listed_writer(&account.pubkey(), &writer.pubkey(), 0, &[1u8; 8]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &writer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let access_list_account = context
+        .banks_client
+        .get_account(access_list)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(access_list_account.owner, id());
+    assert_eq!(
+        access_list_account.lamports,
+        Rent::default().minimum_balance(AccessList::SIZE)
+    );
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
+        &[1u8; 8]
+    );
+}
+
+#[tokio::test]
+async fn close_account_fail_open_grants() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+    let delegate = Keypair::new();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::grant_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                8,
+                Delegation::NO_EXPIRY,
+            ),
+            instruction::close_account(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(RecordError::OpenGrants as u32)
+        )
+    );
+
+    // once the delegation is revoked, the record can be closed
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::grant_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                8,
+                Delegation::NO_EXPIRY,
+            ),
+            instruction::revoke_delegation(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &delegate.pubkey(),
+                &context.payer.pubkey(),
+            ),
+            instruction::close_account(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn freeze_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::freeze(&account.pubkey(), &authority.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let account_data = RecordData::unpack(&account_handle.data).unwrap();
+    assert!(account_data.is_immutable());
+
+    // the former authority cannot change the record anymore
+    for instruction in [
+        instruction::write(&account.pubkey(), &authority.pubkey(), 0, &[200u8; 8]),
+        instruction::set_authority(&account.pubkey(), &authority.pubkey(), &authority.pubkey()),
+        instruction::close_account(&account.pubkey(), &authority.pubkey(), &authority.pubkey()),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::Immutable as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn reallocate_success() {
+    let receiver = Pubkey::new_unique();
+    let receiver_starting_lamports = 1_000_000_000;
+    let mut program_test = program_test();
+    program_test.add_account(
+        receiver,
+        Account {
+            lamports: receiver_starting_lamports,
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let new_data_length = 16u64;
+    let expected_account_data_length = RecordData::WRITABLE_START_INDEX
+        .checked_add(new_data_length as usize)
+        .unwrap();
+
+    // the payer tops up rent while growing
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::reallocate(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            &receiver,
+            new_data_length,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account_handle = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(account_handle.data.len(), expected_account_data_length);
+    assert_eq!(
+        account_handle.lamports,
+        Rent::default().minimum_balance(expected_account_data_length)
+    );
+
+    // reallocate to a smaller length, refunding the excess rent
+    let old_data_length = 4u64;
+    let old_account_data_length = RecordData::WRITABLE_START_INDEX
+        .checked_add(old_data_length as usize)
+        .unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::reallocate(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            &receiver,
+            old_data_length,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(account.data.len(), old_account_data_length);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(old_account_data_length)
+    );
+    assert_eq!(
+        &account.data[RecordData::WRITABLE_START_INDEX..],
+        &data[..4]
+    );
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!({ account_data.length }, old_data_length);
+
+    let receiver = context
+        .banks_client
+        .get_account(receiver)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        receiver.lamports,
+        receiver_starting_lamports + Rent::default().minimum_balance(expected_account_data_length)
+            - Rent::default().minimum_balance(old_account_data_length)
+    );
+}
+
+#[tokio::test]
+async fn reallocate_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let new_data_length = 16u64;
+    let delta_account_data_length = new_data_length.saturating_sub(data.len() as u64);
+    let additional_lamports_needed =
+        Rent::default().minimum_balance(delta_account_data_length as usize);
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction {
+                program_id: id(),
+                accounts: vec![
+                    AccountMeta::new(account.pubkey(), false),
+                    AccountMeta::new(wrong_authority.pubkey(), true),
+                    AccountMeta::new(context.payer.pubkey(), true),
+                    AccountMeta::new(context.payer.pubkey(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+                data: instruction::RecordInstruction::Reallocate {
+                    data_length: new_data_length,
+                }
+                .pack(),
+            },
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &account.pubkey(),
+                additional_lamports_needed,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn reallocate_fail_unsigned() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    let new_data_length = 16u64;
+    let delta_account_data_length = new_data_length.saturating_sub(data.len() as u64);
+    let additional_lamports_needed =
+        Rent::default().minimum_balance(delta_account_data_length as usize);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            Instruction {
+                program_id: id(),
+                accounts: vec![
+                    AccountMeta::new(account.pubkey(), false),
+                    AccountMeta::new(authority.pubkey(), false),
+                    AccountMeta::new(context.payer.pubkey(), true),
+                    AccountMeta::new(context.payer.pubkey(), false),
+                    AccountMeta::new_readonly(solana_system_interface::program::id(), false),
+                ],
+                data: instruction::RecordInstruction::Reallocate {
+                    data_length: new_data_length,
+                }
+                .pack(),
+            },
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &account.pubkey(),
+                additional_lamports_needed,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn append_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    // appending past the end of the account grows it, the payer covers rent
+    let appended = &[200u8; 24];
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::append(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                appended,
+            ),
+            instruction::append(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                &[1, 2, 3],
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
         .banks_client
         .get_account(account.pubkey())
         .await
         .unwrap()
         .unwrap();
-    let account_data = bytemuck::try_from_bytes::<RecordData>(
-        &account_handle.data[..RecordData::WRITABLE_START_INDEX],
-    )
-    .unwrap();
-    assert_eq!(account_data.authority, new_authority.pubkey());
+    let expected_length = data.len() + appended.len() + 3;
+    assert_eq!(
+        account.data.len(),
+        RecordData::WRITABLE_START_INDEX + expected_length
+    );
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
 
-    let new_data = &[200u8; 8];
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!({ account_data.length }, expected_length as u64);
+
+    let mut expected = data.to_vec();
+    expected.extend_from_slice(appended);
+    expected.extend_from_slice(&[1, 2, 3]);
+    assert_eq!(&account.data[RecordData::WRITABLE_START_INDEX..], expected);
+}
+
+#[tokio::test]
+async fn append_within_account_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[9u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
+
+    // appends go after the data in use, not at the end of the account
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction::write(
-            &account.pubkey(),
-            &new_authority.pubkey(),
-            0,
-            new_data,
-        )],
+        &[
+            instruction::reallocate(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                &context.payer.pubkey(),
+                16,
+            ),
+            instruction::append(
+                &account.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                &[7u8; 4],
+            ),
+        ],
         Some(&context.payer.pubkey()),
-        &[&context.payer, &new_authority],
+        &[&context.payer, &authority],
         context.last_blockhash,
     );
     context
@@ -431,26 +2270,23 @@ async fn set_authority_success() {
         .await
         .unwrap();
 
-    let account_handle = context
+    let account = context
         .banks_client
         .get_account(account.pubkey())
         .await
         .unwrap()
         .unwrap();
-    let account_data = bytemuck::try_from_bytes::<RecordData>(
-        &account_handle.data[..RecordData::WRITABLE_START_INDEX],
-    )
-    .unwrap();
-    assert_eq!(account_data.authority, new_authority.pubkey());
-    assert_eq!(account_data.version, RecordData::CURRENT_VERSION);
+    assert_eq!(account.data.len(), RecordData::WRITABLE_START_INDEX + 16);
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!({ account_data.length }, 12);
     assert_eq!(
-        &account_handle.data[RecordData::WRITABLE_START_INDEX..],
-        new_data,
+        &account.data[RecordData::WRITABLE_START_INDEX..],
+        &[9, 9, 9, 9, 9, 9, 9, 9, 7, 7, 7, 7, 0, 0, 0, 0]
     );
 }
 
 #[tokio::test]
-async fn set_authority_fail_wrong_authority() {
+async fn append_fail_wrong_authority() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -460,15 +2296,12 @@ async fn set_authority_fail_wrong_authority() {
 
     let wrong_authority = Keypair::new();
     let transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id: id(),
-            accounts: vec![
-                AccountMeta::new(account.pubkey(), false),
-                AccountMeta::new_readonly(wrong_authority.pubkey(), true),
-                AccountMeta::new(Pubkey::new_unique(), false),
-            ],
-            data: instruction::RecordInstruction::SetAuthority.pack(),
-        }],
+        &[instruction::append(
+            &account.pubkey(),
+            &wrong_authority.pubkey(),
+            &context.payer.pubkey(),
+            &[200u8; 8],
+        )],
         Some(&context.payer.pubkey()),
         &[&context.payer, &wrong_authority],
         context.last_blockhash,
@@ -488,7 +2321,7 @@ async fn set_authority_fail_wrong_authority() {
 }
 
 #[tokio::test]
-async fn set_authority_fail_unsigned() {
+async fn write_if_match_success() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -496,33 +2329,39 @@ async fn set_authority_fail_unsigned() {
     let data = &[222u8; 8];
     initialize_storage_account(&mut context, &authority, &account, data).await;
 
+    let new_data = &[200u8; 4];
     let transaction = Transaction::new_signed_with_payer(
-        &[Instruction {
-            program_id: id(),
-            accounts: vec![
-                AccountMeta::new(account.pubkey(), false),
-                AccountMeta::new_readonly(authority.pubkey(), false),
-                AccountMeta::new(Pubkey::new_unique(), false),
-            ],
-            data: instruction::RecordInstruction::SetAuthority.pack(),
-        }],
+        &[instruction::write_if_match(
+            &account.pubkey(),
+            &authority.pubkey(),
+            2,
+            RecordData::hash_contents(&data[2..6]),
+            new_data,
+        )],
         Some(&context.payer.pubkey()),
-        &[&context.payer],
+        &[&context.payer, &authority],
         context.last_blockhash,
     );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        context
-            .banks_client
-            .process_transaction(transaction)
-            .await
-            .unwrap_err()
-            .unwrap(),
-        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+        &account.data[RecordData::WRITABLE_START_INDEX..],
+        &[222, 222, 200, 200, 200, 200, 222, 222]
     );
 }
 
 #[tokio::test]
-async fn reallocate_success() {
+async fn write_if_match_fail_changed_contents() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -530,50 +2369,48 @@ async fn reallocate_success() {
     let data = &[222u8; 8];
     initialize_storage_account(&mut context, &authority, &account, data).await;
 
-    let new_data_length = 16u64;
-    let expected_account_data_length = RecordData::WRITABLE_START_INDEX
-        .checked_add(new_data_length as usize)
-        .unwrap();
-
-    let delta_account_data_length = new_data_length.saturating_sub(data.len() as u64);
-    let additional_lamports_needed =
-        Rent::default().minimum_balance(delta_account_data_length as usize);
-
+    // another operator updated the record after this write was prepared
+    let stale_hash = RecordData::hash_contents(&[111u8; 8]);
     let transaction = Transaction::new_signed_with_payer(
-        &[
-            instruction::reallocate(&account.pubkey(), &authority.pubkey(), new_data_length),
-            system_instruction::transfer(
-                &context.payer.pubkey(),
-                &account.pubkey(),
-                additional_lamports_needed,
-            ),
-        ],
+        &[instruction::write_if_match(
+            &account.pubkey(),
+            &authority.pubkey(),
+            0,
+            stale_hash,
+            &[200u8; 8],
+        )],
         Some(&context.payer.pubkey()),
         &[&context.payer, &authority],
         context.last_blockhash,
     );
-    context
-        .banks_client
-        .process_transaction(transaction)
-        .await
-        .unwrap();
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::ContentMismatch as u32)
+        )
+    );
+}
 
-    let account_handle = context
-        .banks_client
-        .get_account(account.pubkey())
-        .await
-        .unwrap()
-        .unwrap();
+#[tokio::test]
+async fn write_batch_success() {
+    let mut context = program_test().start_with_context().await;
 
-    assert_eq!(account_handle.data.len(), expected_account_data_length);
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[222u8; 8];
+    initialize_storage_account(&mut context, &authority, &account, data).await;
 
-    // reallocate to a smaller length
-    let old_data_length = 8u64;
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction::reallocate(
+        &[instruction::write_batch(
             &account.pubkey(),
             &authority.pubkey(),
-            old_data_length,
+            &[(0, &[1u8, 2]), (5, &[3u8]), (7, &[4u8])],
         )],
         Some(&context.payer.pubkey()),
         &[&context.payer, &authority],
@@ -591,12 +2428,17 @@ async fn reallocate_success() {
         .await
         .unwrap()
         .unwrap();
-
-    assert_eq!(account.data.len(), expected_account_data_length);
+    assert_eq!(
+        &account.data[RecordData::WRITABLE_START_INDEX..],
+        &[1, 2, 222, 222, 222, 3, 222, 4]
+    );
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    // the initial write and a single batch
+    assert_eq!({ account_data.sequence }, 2);
 }
 
 #[tokio::test]
-async fn reallocate_fail_wrong_authority() {
+async fn write_batch_fail_out_of_bounds() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -604,36 +2446,16 @@ async fn reallocate_fail_wrong_authority() {
     let data = &[222u8; 8];
     initialize_storage_account(&mut context, &authority, &account, data).await;
 
-    let new_data_length = 16u64;
-    let delta_account_data_length = new_data_length.saturating_sub(data.len() as u64);
-    let additional_lamports_needed =
-        Rent::default().minimum_balance(delta_account_data_length as usize);
-
-    let wrong_authority = Keypair::new();
     let transaction = Transaction::new_signed_with_payer(
-        &[
-            Instruction {
-                program_id: id(),
-                accounts: vec![
-                    AccountMeta::new(account.pubkey(), false),
-                    AccountMeta::new(wrong_authority.pubkey(), true),
-                ],
-                data: instruction::RecordInstruction::Reallocate {
-                    data_length: new_data_length,
-                }
-                .pack(),
-            },
-            system_instruction::transfer(
-                &context.payer.pubkey(),
-                &account.pubkey(),
-                additional_lamports_needed,
-            ),
-        ],
+        &[instruction::write_batch(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &[(0, &[1u8, 2]), (7, &[3u8, 4])],
+        )],
         Some(&context.payer.pubkey()),
-        &[&context.payer, &wrong_authority],
+        &[&context.payer, &authority],
         context.last_blockhash,
     );
-
     assert_eq!(
         context
             .banks_client
@@ -643,13 +2465,76 @@ async fn reallocate_fail_wrong_authority() {
             .unwrap(),
         TransactionError::InstructionError(
             0,
-            InstructionError::Custom(RecordError::IncorrectAuthority as u32)
+            InstructionError::Custom(RecordError::OutOfBounds as u32)
         )
     );
 }
 
 #[tokio::test]
-async fn reallocate_fail_unsigned() {
+async fn migrate_success() {
+    let authority = Keypair::new();
+    let account = Pubkey::new_unique();
+    let data = &[111u8; 8];
+
+    // record created by a program version with the v1 header
+    let mut v1_data = bytemuck::bytes_of(&RecordDataV1 {
+        version: RecordDataV1::VERSION,
+        authority: authority.pubkey(),
+    })
+    .to_vec();
+    v1_data.extend_from_slice(data);
+    let mut program_test = program_test();
+    program_test.add_account(
+        account,
+        Account {
+            lamports: Rent::default().minimum_balance(v1_data.len()),
+            data: v1_data,
+            owner: id(),
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::migrate(&account, &authority.pubkey(), &context.payer.pubkey()),
+            instruction::write(&account, &authority.pubkey(), 6, &[200u8; 2]),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        account.data.len(),
+        RecordData::WRITABLE_START_INDEX + data.len()
+    );
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
+
+    let account_data = RecordData::unpack(&account.data).unwrap();
+    assert_eq!(account_data.version, RecordData::CURRENT_VERSION);
+    assert_eq!(account_data.authority, authority.pubkey());
+    assert_eq!({ account_data.length }, data.len() as u64);
+    assert_eq!({ account_data.sequence }, 2);
+    assert_eq!(
+        &account.data[RecordData::WRITABLE_START_INDEX..],
+        &[111, 111, 111, 111, 111, 111, 200, 200]
+    );
+}
+
+#[tokio::test]
+async fn migrate_fail_current_version() {
     let mut context = program_test().start_with_context().await;
 
     let authority = Keypair::new();
@@ -657,35 +2542,16 @@ async fn reallocate_fail_unsigned() {
     let data = &[222u8; 8];
     initialize_storage_account(&mut context, &authority, &account, data).await;
 
-    let new_data_length = 16u64;
-    let delta_account_data_length = new_data_length.saturating_sub(data.len() as u64);
-    let additional_lamports_needed =
-        Rent::default().minimum_balance(delta_account_data_length as usize);
-
     let transaction = Transaction::new_signed_with_payer(
-        &[
-            Instruction {
-                program_id: id(),
-                accounts: vec![
-                    AccountMeta::new(account.pubkey(), false),
-                    AccountMeta::new(authority.pubkey(), false),
-                ],
-                data: instruction::RecordInstruction::Reallocate {
-                    data_length: new_data_length,
-                }
-                .pack(),
-            },
-            system_instruction::transfer(
-                &context.payer.pubkey(),
-                &account.pubkey(),
-                additional_lamports_needed,
-            ),
-        ],
+        &[instruction::migrate(
+            &account.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+        )],
         Some(&context.payer.pubkey()),
-        &[&context.payer],
+        &[&context.payer, &authority],
         context.last_blockhash,
     );
-
     assert_eq!(
         context
             .banks_client
@@ -693,6 +2559,9 @@ async fn reallocate_fail_unsigned() {
             .await
             .unwrap_err()
             .unwrap(),
-        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::AlreadyCurrentVersion as u32)
+        )
     );
 }
@@ -0,0 +1,185 @@
+//! Property tests of the instruction wire format
+use {
+    all2all_controller::instruction::RecordInstruction,
+    proptest::{collection::vec, prelude::*, test_runner::TestCaseError},
+    solana_pubkey::Pubkey,
+};
+
+fn assert_round_trip(instruction: RecordInstruction) -> Result<(), TestCaseError> {
+    let packed = instruction.pack();
+    prop_assert_eq!(RecordInstruction::unpack(&packed), Ok(instruction));
+    Ok(())
+}
+
+fn bytes() -> impl Strategy<Value = Vec<u8>> {
+    vec(any::<u8>(), 0..256)
+}
+
+#[test]
+fn round_trip_unit_variants() {
+    for instruction in [
+        RecordInstruction::Initialize,
+        RecordInstruction::SetAuthority,
+        RecordInstruction::CloseAccount,
+        RecordInstruction::ApproveProposal,
+        RecordInstruction::ProposeNestedApproval,
+        RecordInstruction::ApproveWithSignatures,
+        RecordInstruction::PauseMultisig,
+        RecordInstruction::ProposeUnpauseMultisig,
+        RecordInstruction::CloseProposal,
+        RecordInstruction::FinalizeProposalBuffer,
+        RecordInstruction::Migrate,
+        RecordInstruction::Freeze,
+        RecordInstruction::NominateAuthority,
+        RecordInstruction::AcceptAuthority,
+        RecordInstruction::CancelAuthorityNomination,
+        RecordInstruction::RevokeDelegation,
+        RecordInstruction::AddWriter,
+        RecordInstruction::RemoveWriter,
+        RecordInstruction::MigrateMultisig,
+        RecordInstruction::MigrateProposal,
+        RecordInstruction::PauseRecord,
+    ] {
+        assert_round_trip(instruction).unwrap();
+    }
+}
+
+proptest! {
+    #[test]
+    fn round_trip_write(offset in any::<u64>(), data in bytes()) {
+        assert_round_trip(RecordInstruction::Write { offset, data: &data })?;
+    }
+
+    #[test]
+    fn round_trip_reallocate(data_length in any::<u64>()) {
+        assert_round_trip(RecordInstruction::Reallocate { data_length })?;
+    }
+
+    #[test]
+    fn round_trip_propose_multi_write(
+        offset in any::<u64>(),
+        data in bytes(),
+        description in bytes(),
+    ) {
+        assert_round_trip(RecordInstruction::ProposeMultiWrite {
+            offset,
+            data: &data,
+            description: &description,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_approve_with_eth_signature(
+        recovery_id in any::<u8>(),
+        signature in vec(any::<u8>(), 64),
+    ) {
+        assert_round_trip(RecordInstruction::ApproveWithEthSignature {
+            recovery_id,
+            signature: signature.try_into().unwrap(),
+        })?;
+    }
+
+    #[test]
+    fn round_trip_create_proposal_buffer(description in bytes()) {
+        assert_round_trip(RecordInstruction::CreateProposalBuffer {
+            description: &description,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_propose_unpause_record(description in bytes()) {
+        assert_round_trip(RecordInstruction::ProposeUnpauseRecord {
+            description: &description,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_propose_set_guardian(guardian in any::<[u8; 32]>(), description in bytes()) {
+        assert_round_trip(RecordInstruction::ProposeSetGuardian {
+            guardian: Pubkey::new_from_array(guardian),
+            description: &description,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_write_proposal_buffer(offset in any::<u64>(), data in bytes()) {
+        assert_round_trip(RecordInstruction::WriteProposalBuffer { offset, data: &data })?;
+    }
+
+    #[test]
+    fn round_trip_append(data in bytes()) {
+        assert_round_trip(RecordInstruction::Append { data: &data })?;
+    }
+
+    #[test]
+    fn round_trip_write_if_match(
+        offset in any::<u64>(),
+        expected_hash in any::<[u8; 32]>(),
+        data in bytes(),
+    ) {
+        assert_round_trip(RecordInstruction::WriteIfMatch {
+            offset,
+            expected_hash,
+            data: &data,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_propose_multi_write_if_match(
+        offset in any::<u64>(),
+        expected_hash in any::<[u8; 32]>(),
+        data in bytes(),
+        description in bytes(),
+    ) {
+        assert_round_trip(RecordInstruction::ProposeMultiWriteIfMatch {
+            offset,
+            expected_hash,
+            data: &data,
+            description: &description,
+        })?;
+    }
+
+    #[test]
+    fn round_trip_write_batch(segments in vec((any::<u64>(), bytes()), 0..8)) {
+        assert_round_trip(RecordInstruction::WriteBatch {
+            segments: segments
+                .iter()
+                .map(|(offset, data)| (*offset, data.as_slice()))
+                .collect(),
+        })?;
+    }
+
+    #[test]
+    fn round_trip_create_and_initialize(
+        data_length in any::<u64>(),
+        seeds in vec(vec(any::<u8>(), 0..32), 0..16),
+    ) {
+        assert_round_trip(RecordInstruction::CreateAndInitialize {
+            data_length,
+            seeds: seeds.iter().map(Vec::as_slice).collect(),
+        })?;
+    }
+
+    #[test]
+    fn round_trip_grant_delegation(
+        start in any::<u64>(),
+        end in any::<u64>(),
+        expiry_slot in any::<u64>(),
+    ) {
+        assert_round_trip(RecordInstruction::GrantDelegation {
+            start,
+            end,
+            expiry_slot,
+        })?;
+    }
+
+    #[test]
+    fn unpack_arbitrary_input(tag in 0u8..35, body in vec(any::<u8>(), 0..512)) {
+        let mut input = vec![tag];
+        input.extend_from_slice(&body);
+        // never panics, and whatever decodes packs back to an equal instruction
+        if let Ok(instruction) = RecordInstruction::unpack(&input) {
+            assert_round_trip(instruction)?;
+        }
+    }
+}
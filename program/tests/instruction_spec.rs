@@ -0,0 +1,93 @@
+//! Keeps `instruction-spec.json` in sync with the instruction encoding
+use {
+    all2all_controller::instruction::{RecordInstruction, RecordInstructionTag},
+    multisig::proposal::Proposal,
+    serde_json::Value,
+};
+
+fn spec() -> Value {
+    serde_json::from_str(include_str!("../instruction-spec.json")).unwrap()
+}
+
+/// encode a sample value of a spec field type
+fn sample_field(buf: &mut Vec<u8>, field_type: &str) {
+    let data = [7u8; 3];
+    match field_type {
+        "u8" => buf.push(1),
+        "u64" => buf.extend_from_slice(&2u64.to_le_bytes()),
+        "[u8; 32]" => buf.extend_from_slice(&[3; 32]),
+        "[u8; 64]" => buf.extend_from_slice(&[4; 64]),
+        "bytes" => {
+            buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            buf.extend_from_slice(&data);
+        }
+        "description" => {
+            buf.extend_from_slice(&(data.len() as u16).to_le_bytes());
+            buf.extend_from_slice(&data);
+        }
+        "segments" => {
+            buf.extend_from_slice(&1u16.to_le_bytes());
+            sample_field(buf, "u64");
+            sample_field(buf, "bytes");
+        }
+        "seeds" => {
+            buf.push(1);
+            buf.push(data.len() as u8);
+            buf.extend_from_slice(&data);
+        }
+        _ => panic!("unknown field type {field_type}"),
+    }
+}
+
+#[test]
+fn spec_matches_instruction_tags() {
+    let spec = spec();
+    let instructions = spec["instructions"].as_array().unwrap();
+    for (expected_tag, instruction) in instructions.iter().enumerate() {
+        let tag = u8::try_from(instruction["tag"].as_u64().unwrap()).unwrap();
+        assert_eq!(tag as usize, expected_tag, "tags are listed in order");
+        let name = instruction["name"].as_str().unwrap();
+        assert_eq!(
+            format!("{:?}", RecordInstructionTag::try_from(tag).unwrap()),
+            name
+        );
+    }
+    assert!(RecordInstructionTag::try_from(instructions.len() as u8).is_err());
+}
+
+#[test]
+fn spec_matches_instruction_layout() {
+    for instruction in spec()["instructions"].as_array().unwrap() {
+        let tag = instruction["tag"].as_u64().unwrap() as u8;
+        let mut data = vec![tag];
+        for field in instruction["fields"].as_array().unwrap() {
+            sample_field(&mut data, field["type"].as_str().unwrap());
+        }
+
+        let unpacked = RecordInstruction::unpack(&data).unwrap();
+        assert_eq!(u8::from(unpacked.tag()), tag);
+        assert_eq!(unpacked.pack(), data);
+    }
+}
+
+#[test]
+fn spec_matches_reserved_proposal_tags() {
+    let spec = spec();
+    let reserved: Vec<(&str, u64)> = spec["reserved_proposal_tags"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|tag| (tag["name"].as_str().unwrap(), tag["tag"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(
+        reserved,
+        [
+            ("NestedApproval", Proposal::NESTED_APPROVAL_TAG as u64),
+            ("Unpause", Proposal::UNPAUSE_TAG as u64),
+            ("SetGuardian", Proposal::SET_GUARDIAN_TAG as u64),
+        ]
+    );
+    for (_, tag) in reserved {
+        assert!(RecordInstructionTag::try_from(tag as u8).is_err());
+    }
+}